@@ -0,0 +1,78 @@
+//! Throughput benchmarks for the parsers.
+//!
+//! Run with `cargo bench`. This is a plain `harness = false` benchmark using
+//! `std::time::Instant`, so it needs no extra dependency.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use senpa::batch::parse_logs;
+use senpa::parse_log;
+
+const TCP_LOG: &str = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+    4,0x0,,127,61633,0,DF,6,tcp,\
+    52,192.168.10.15,192.168.20.14,\
+    52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK";
+
+const UDP_LOG: &str = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+    4,0x0,,127,58940,0,none,17,udp,\
+    106,192.168.10.15,192.168.20.11,49678,161,86";
+
+const ICMP_LOG: &str = "97,,,02f4bab031b57d1e30553ce08e0ec131,em0,match,pass,out,\
+    4,0x0,,64,12345,0,none,1,icmp,\
+    84,192.168.1.1,8.8.8.8,request,29474,1";
+
+const CORPUS_LINES: usize = 100_000;
+
+/// Runs `f` for at least `budget` after a short warm-up and reports the mean time per iteration.
+fn bench<F: FnMut()>(name: &str, budget: Duration, mut f: F) {
+    for _ in 0..100 {
+        f();
+    }
+
+    let start = Instant::now();
+    let mut iterations = 0u32;
+    while start.elapsed() < budget {
+        f();
+        iterations += 1;
+    }
+
+    let per_iter = start.elapsed() / iterations;
+    println!(
+        "{:<24} {:>12.2?}/iter ({} iterations)",
+        name, per_iter, iterations
+    );
+}
+
+fn main() {
+    let budget = Duration::from_secs(2);
+
+    for (name, line) in [
+        ("parse_log tcp", TCP_LOG),
+        ("parse_log udp", UDP_LOG),
+        ("parse_log icmp", ICMP_LOG),
+    ] {
+        assert!(parse_log(line).is_ok(), "{} sample must parse", name);
+        bench(name, budget, || {
+            black_box(parse_log(black_box(line)).unwrap());
+        });
+    }
+
+    let corpus = [TCP_LOG, UDP_LOG, ICMP_LOG]
+        .iter()
+        .cycle()
+        .take(CORPUS_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let start = Instant::now();
+    let logs = parse_logs(black_box(&corpus));
+    let elapsed = start.elapsed();
+    assert_eq!(CORPUS_LINES, logs.len());
+    println!(
+        "{:<24} {:>12.2?} ({:.0} lines/s)",
+        "parse_logs 100k lines",
+        elapsed,
+        CORPUS_LINES as f64 / elapsed.as_secs_f64()
+    );
+}
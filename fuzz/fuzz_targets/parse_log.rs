@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// parse_log_bytes must never panic: any input either parses or returns a LogParseError.
+fuzz_target!(|data: &[u8]| {
+    let _ = senpa::log::parse_log_bytes(data);
+});
@@ -0,0 +1,360 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::flow::FiveTuple;
+use crate::log::FwLog;
+use crate::packet_filter::Action;
+use crate::protocol::{ProtoInfo, ProtoName};
+
+/// Sums the bytes transferred per interface.
+///
+/// # Example
+/// ```rust
+/// use senpa::{analysis::bytes_per_interface, parse_log};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+///
+/// let totals = bytes_per_interface(&[log.clone(), log]);
+/// assert_eq!(Some(&212), totals.get("vlan0.20"));
+/// ```
+pub fn bytes_per_interface<'a, I>(logs: I) -> HashMap<String, u64>
+where
+    I: IntoIterator<Item = &'a FwLog>,
+{
+    let mut totals = HashMap::new();
+    for log in logs {
+        *totals
+            .entry(log.packet_filter.interface.clone())
+            .or_insert(0) += u64::from(log.byte_count());
+    }
+    totals
+}
+
+/// Counts the logs per [`FwLog::rule_hit_key`]: which rules fire, with which action, on which interface.
+///
+/// # Example
+/// ```rust
+/// use senpa::{analysis::rule_hits, parse_log, Action};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+///
+/// let hits = rule_hits(&[log.clone(), log]);
+/// assert_eq!(Some(&2), hits.get(&(96, Action::Pass, "vlan0.20".to_string())));
+/// ```
+pub fn rule_hits<'a, I>(logs: I) -> HashMap<(u32, Action, String), u64>
+where
+    I: IntoIterator<Item = &'a FwLog>,
+{
+    let mut hits = HashMap::new();
+    for log in logs {
+        *hits.entry(log.rule_hit_key()).or_insert(0) += 1;
+    }
+    hits
+}
+
+/// Groups logs by interface, keeping their order within each group.
+///
+/// The crate has no `LogFile` collection type, so any iterator of log references is accepted,
+/// such as a `&Vec<FwLog>` of parsed logs.
+///
+/// # Example
+/// ```rust
+/// use senpa::{analysis::by_interface, parse_log};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+///
+/// let logs = [log.clone(), log];
+/// let groups = by_interface(&logs);
+/// assert_eq!(2, groups["vlan0.20"].len());
+/// ```
+pub fn by_interface<'a, I>(logs: I) -> HashMap<String, Vec<&'a FwLog>>
+where
+    I: IntoIterator<Item = &'a FwLog>,
+{
+    let mut groups: HashMap<String, Vec<&'a FwLog>> = HashMap::new();
+    for log in logs {
+        groups
+            .entry(log.packet_filter.interface.clone())
+            .or_default()
+            .push(log);
+    }
+    groups
+}
+
+/// Sums the bytes transferred per rule number.
+pub fn bytes_per_rule<'a, I>(logs: I) -> HashMap<u32, u64>
+where
+    I: IntoIterator<Item = &'a FwLog>,
+{
+    let mut totals = HashMap::new();
+    for log in logs {
+        *totals
+            .entry(log.packet_filter.rule_info.number)
+            .or_insert(0) += u64::from(log.byte_count());
+    }
+    totals
+}
+
+///A run of consecutive logs of the same flow, as produced by [`flow_merge`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowSummary {
+    /// The [`FwLog::flow_hash`] shared by the merged logs.
+    pub flow_hash: u64,
+    /// The flow tuple of the first merged log.
+    pub five_tuple: FiveTuple,
+    /// The number of merged logs.
+    pub packets: u64,
+    /// The bytes of the merged logs.
+    pub bytes: u64,
+}
+
+/// Collapses consecutive logs sharing the same [`FwLog::flow_hash`] into [`FlowSummary`]s.
+///
+/// A summary holds at most `max_packets` logs, a longer run starts a new summary.
+/// Logs carry no timestamps, so bursts are delimited by count only.
+///
+/// # Example
+/// ```rust
+/// use senpa::{analysis::flow_merge, parse_log};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+///
+/// let summaries = flow_merge(&[log.clone(), log.clone(), log], 2);
+/// assert_eq!(2, summaries.len());
+/// assert_eq!(2, summaries[0].packets);
+/// assert_eq!(106, summaries[1].bytes);
+/// ```
+pub fn flow_merge<'a, I>(logs: I, max_packets: u64) -> Vec<FlowSummary>
+where
+    I: IntoIterator<Item = &'a FwLog>,
+{
+    let mut summaries: Vec<FlowSummary> = Vec::new();
+    for log in logs {
+        let flow_hash = log.flow_hash();
+        match summaries.last_mut() {
+            Some(last) if last.flow_hash == flow_hash && last.packets < max_packets => {
+                last.packets += 1;
+                last.bytes += u64::from(log.byte_count());
+            }
+            _ => summaries.push(FlowSummary {
+                flow_hash,
+                five_tuple: log.five_tuple(),
+                packets: 1,
+                bytes: u64::from(log.byte_count()),
+            }),
+        }
+    }
+    summaries
+}
+
+/// Returns the TCP segments that repeat the [`FwLog::flow_hash`] and sequence number of an
+/// earlier segment in `logs`: likely retransmissions. The first occurrence is not returned.
+///
+/// Segments carrying no data and neither SYN nor FIN are skipped, as consecutive pure ACKs
+/// legitimately share a sequence number.
+///
+/// # Example
+/// ```rust
+/// use senpa::{analysis::find_retransmissions, parse_log};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,61633,0,DF,6,tcp,\
+/// 52,192.168.10.15,192.168.20.14,\
+/// 52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK").unwrap();
+///
+/// let logs = [log.clone(), log];
+/// assert_eq!(vec![&logs[1]], find_retransmissions(&logs));
+/// ```
+pub fn find_retransmissions(logs: &[FwLog]) -> Vec<&FwLog> {
+    let mut seen = HashSet::new();
+    let mut retransmissions = Vec::new();
+    for log in logs {
+        let ProtoInfo::TcpInfo(tcp_info) = &log.proto_info else {
+            continue;
+        };
+        let flags = tcp_info.tcp_flags();
+        if tcp_info.data_len == 0 && !flags.syn && !flags.fin {
+            continue;
+        }
+        if !seen.insert((log.flow_hash(), tcp_info.sequence_number.as_str())) {
+            retransmissions.push(log);
+        }
+    }
+    retransmissions
+}
+
+///Tallies of the protocols the parser has no structured support for.
+///
+/// Use it to find which protocol parsers real traffic needs most.
+///
+/// # Example
+/// ```rust
+/// use senpa::{analysis::UnknownProtoReport, parse_log};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,igb0,match,pass,in,\
+/// 4,0x0,,64,4321,0,none,47,gre,\
+/// 120,203.0.113.1,198.51.100.1,").unwrap();
+///
+/// let mut report = UnknownProtoReport::default();
+/// report.record(&log);
+/// assert_eq!(Some(&1), report.protocols.get("gre"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UnknownProtoReport {
+    /// Occurrences of each `ProtoName::Other` name.
+    pub protocols: HashMap<String, u64>,
+    /// Occurrences of each raw `ProtoInfo::UnknownInfo` payload.
+    pub payloads: HashMap<String, u64>,
+}
+
+impl UnknownProtoReport {
+    /// Counts the protocol name and payload of `log` if they are unknown.
+    pub fn record(&mut self, log: &FwLog) {
+        if let ProtoName::Other(name) = &log.protocol.name {
+            *self.protocols.entry(name.clone()).or_insert(0) += 1;
+        }
+        if let ProtoInfo::UnknownInfo(payload) = &log.proto_info {
+            *self.payloads.entry(payload.clone()).or_insert(0) += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_log;
+
+    fn sample_logs() -> Vec<FwLog> {
+        let tcp = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,61633,0,DF,6,tcp,\
+            52,192.168.10.15,192.168.20.14,\
+            52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK",
+        )
+        .unwrap();
+        let udp = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,58940,0,none,17,udp,\
+            106,192.168.10.15,192.168.20.11,49678,161,86",
+        )
+        .unwrap();
+        let mut other_rule = udp.clone();
+        other_rule.packet_filter.rule_info.number = 15;
+        other_rule.packet_filter.interface = "igb0".into();
+
+        vec![tcp, udp, other_rule]
+    }
+
+    #[test]
+    fn bytes_per_interface_test() {
+        let totals = bytes_per_interface(&sample_logs());
+        assert_eq!(2, totals.len());
+        assert_eq!(Some(&158), totals.get("vlan0.20"));
+        assert_eq!(Some(&106), totals.get("igb0"));
+    }
+
+    #[test]
+    fn rule_hits_test() {
+        let logs = sample_logs();
+        let hits = rule_hits(&logs);
+        assert_eq!(2, hits.len());
+        assert_eq!(Some(&2), hits.get(&(96, Action::Pass, "vlan0.20".into())));
+        assert_eq!(Some(&1), hits.get(&(15, Action::Pass, "igb0".into())));
+    }
+
+    #[test]
+    fn by_interface_test() {
+        let logs = sample_logs();
+        let groups = by_interface(&logs);
+        assert_eq!(2, groups.len());
+        assert_eq!(vec![&logs[0], &logs[1]], groups["vlan0.20"]);
+        assert_eq!(vec![&logs[2]], groups["igb0"]);
+    }
+
+    #[test]
+    fn flow_merge_test() {
+        let logs = sample_logs();
+        let (tcp, udp) = (&logs[0], &logs[1]);
+        let reply = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,in,\
+            4,0x0,,64,1234,0,none,17,udp,\
+            106,192.168.20.11,192.168.10.15,161,49678,86",
+        )
+        .unwrap();
+
+        let summaries = flow_merge([tcp, udp, &reply, udp, tcp], 10);
+        let packets: Vec<u64> = summaries.iter().map(|summary| summary.packets).collect();
+        assert_eq!(vec![1, 3, 1], packets);
+        assert_eq!(318, summaries[1].bytes);
+        assert_eq!(udp.five_tuple(), summaries[1].five_tuple);
+
+        assert_eq!(2, flow_merge([udp, udp, udp], 2).len());
+    }
+
+    #[test]
+    fn find_retransmissions_test() {
+        let logs = sample_logs();
+        let data = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,61634,0,DF,6,tcp,\
+            552,192.168.10.15,192.168.20.14,\
+            52461,9100,500,PA,3442468762:3442469262,1,502,,nop;nop;TS",
+        )
+        .unwrap();
+        let ack = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,61635,0,DF,6,tcp,\
+            52,192.168.10.15,192.168.20.14,\
+            52461,9100,0,A,3442469262,1,502,,nop;nop;TS",
+        )
+        .unwrap();
+        let mut other_flow = data.clone();
+        other_flow.ip_data.dst = "192.168.20.15".parse().unwrap();
+
+        let batch = vec![
+            logs[0].clone(),
+            data.clone(),
+            ack.clone(),
+            ack,
+            logs[1].clone(),
+            other_flow,
+            data,
+            logs[0].clone(),
+        ];
+        assert_eq!(vec![&batch[6], &batch[7]], find_retransmissions(&batch));
+    }
+
+    #[test]
+    fn unknown_proto_report_test() {
+        let gre = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,igb0,match,pass,in,\
+            4,0x0,,64,4321,0,none,47,gre,\
+            120,203.0.113.1,198.51.100.1,",
+        )
+        .unwrap();
+
+        let mut report = UnknownProtoReport::default();
+        for log in sample_logs().iter().chain([&gre, &gre]) {
+            report.record(log);
+        }
+
+        assert_eq!(1, report.protocols.len());
+        assert_eq!(Some(&2), report.protocols.get("gre"));
+        assert_eq!(Some(&2), report.payloads.get(""));
+    }
+
+    #[test]
+    fn bytes_per_rule_test() {
+        let totals = bytes_per_rule(&sample_logs());
+        assert_eq!(2, totals.len());
+        assert_eq!(Some(&158), totals.get(&96));
+        assert_eq!(Some(&106), totals.get(&15));
+    }
+}
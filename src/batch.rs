@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::analysis::UnknownProtoReport;
+use crate::log::{parse_log, parse_log_with_rest, FwLog, LogParseError};
+use crate::options::ParseOptions;
+
+/// Parses every non-blank line of `input`, in order.
+///
+/// # Example
+/// ```rust
+/// use senpa::batch::parse_logs;
+///
+/// let input = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86\n\
+/// \n\
+/// not a log\n";
+///
+/// let logs = parse_logs(input);
+/// assert_eq!(2, logs.len());
+/// assert!(logs[0].is_ok());
+/// assert!(logs[1].is_err());
+/// ```
+pub fn parse_logs(input: &str) -> Vec<Result<FwLog, LogParseError>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_log)
+        .collect()
+}
+
+/// Parses every non-blank line of `input` like [`parse_logs`],
+/// recording the unknown protocols of the parsed logs in `report`.
+pub fn parse_logs_reporting(
+    input: &str,
+    report: &mut UnknownProtoReport,
+) -> Vec<Result<FwLog, LogParseError>> {
+    let logs = parse_logs(input);
+    for log in logs.iter().flatten() {
+        report.record(log);
+    }
+    logs
+}
+
+///How well the parser handles a log source, as returned by [`analyze`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CoverageReport {
+    /// The number of non-blank lines.
+    pub lines: usize,
+    /// The number of lines parsed successfully.
+    pub parsed: usize,
+    /// The failed lines, counted per [`LogParseError::reason`], which names the stage that failed.
+    pub failures: HashMap<String, usize>,
+    /// The parsed lines per protocol name.
+    pub protocols: HashMap<String, usize>,
+    /// The number of parsed lines whose payload was kept raw in `ProtoInfo::UnknownInfo`.
+    pub unknown_payloads: usize,
+}
+
+/// Parses every non-blank line of `input` and reports how many parse and why the others fail.
+///
+/// Run it first on the output of a new firewall to check how well the parser covers it.
+///
+/// # Example
+/// ```rust
+/// use senpa::batch::analyze;
+///
+/// let input = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86\n\
+/// not a log\n";
+///
+/// let report = analyze(input);
+/// assert_eq!(2, report.lines);
+/// assert_eq!(1, report.parsed);
+/// assert_eq!(Some(&1), report.failures.get("Failed to parse packet filter"));
+/// assert_eq!(Some(&1), report.protocols.get("udp"));
+/// ```
+pub fn analyze(input: &str) -> CoverageReport {
+    let mut report = CoverageReport::default();
+    for log in parse_logs(input) {
+        report.lines += 1;
+        match log {
+            Ok(log) => {
+                report.parsed += 1;
+                *report
+                    .protocols
+                    .entry(log.protocol.name.to_string())
+                    .or_insert(0) += 1;
+                if !log.proto_info.is_known() {
+                    report.unknown_payloads += 1;
+                }
+            }
+            Err(err) => *report.failures.entry(err.reason).or_insert(0) += 1,
+        }
+    }
+    report
+}
+
+/// Parses at most the first `n` non-blank lines of `input`, to inspect a sample of a large log.
+///
+/// Lines after the sample are not read.
+pub fn parse_sample(input: &str, n: usize) -> Vec<Result<FwLog, LogParseError>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(n)
+        .map(parse_log)
+        .collect()
+}
+
+/// Parses a line holding several log entries concatenated without a separator,
+/// as produced by some broken log shippers.
+///
+/// Entries are split with [`parse_log_with_rest`]. Parsing stops at the first entry that fails,
+/// which is returned last with the remaining input as its raw log.
+pub fn parse_concatenated(input: &str) -> Vec<Result<FwLog, LogParseError>> {
+    let options = ParseOptions::default();
+    let mut logs = Vec::new();
+    let mut next = input.trim();
+
+    while !next.is_empty() {
+        match parse_log_with_rest(next, &options) {
+            Ok((log, rest)) => {
+                logs.push(Ok(log));
+                next = rest;
+            }
+            Err(err) => {
+                logs.push(Err(err));
+                break;
+            }
+        }
+    }
+
+    logs
+}
+
+/// The logs parsed from a file, or the error that prevented reading it.
+pub type FileLogs = io::Result<Vec<Result<FwLog, LogParseError>>>;
+
+/// Parses several log files across threads.
+///
+/// Results are returned in the order of `paths`, and each file keeps the order of its lines.
+/// Files are distributed among at most [`thread::available_parallelism`] worker threads.
+pub fn parse_files_parallel(paths: &[PathBuf]) -> Vec<(PathBuf, FileLogs)> {
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(paths.len());
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<FileLogs>>> = Mutex::new(paths.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(i) else {
+                    break;
+                };
+
+                let logs = fs::read_to_string(path).map(|content| parse_logs(&content));
+                results.lock().unwrap()[i] = Some(logs);
+            });
+        }
+    });
+
+    paths
+        .iter()
+        .cloned()
+        .zip(results.into_inner().unwrap())
+        .map(|(path, logs)| (path, logs.expect("every file is parsed by a worker")))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    const UDP_LOG: &str = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+
+    #[test]
+    fn parse_logs_skips_blank_lines() {
+        let input = format!("{}\n\n  \n{}\r\n", UDP_LOG, UDP_LOG);
+        let logs = parse_logs(&input);
+        assert_eq!(2, logs.len());
+        assert!(logs.iter().all(|log| log.is_ok()));
+    }
+
+    #[test]
+    fn analyze_test() {
+        let gre = "96,,,fae559338f65e11c53669fc3642c93c2,igb0,match,pass,in,\
+            4,0x0,,64,4321,0,none,47,gre,\
+            120,203.0.113.1,198.51.100.1,";
+        let bad_ip = "96,,,fae559338f65e11c53669fc3642c93c2,igb0,match,pass,in,9,";
+        let input = format!(
+            "{}\n{}\n\n{}\nnot a log\n{}\n",
+            UDP_LOG, gre, UDP_LOG, bad_ip
+        );
+
+        let report = analyze(&input);
+        assert_eq!(5, report.lines);
+        assert_eq!(3, report.parsed);
+        assert_eq!(2, report.failures.len());
+        assert_eq!(Some(&1), report.failures.get("Failed to parse IP header"));
+        assert_eq!(Some(&2), report.protocols.get("udp"));
+        assert_eq!(Some(&1), report.protocols.get("gre"));
+        assert_eq!(1, report.unknown_payloads);
+    }
+
+    #[test]
+    fn parse_logs_reporting_counts_unknown() {
+        let gre = "96,,,fae559338f65e11c53669fc3642c93c2,igb0,match,pass,in,\
+            4,0x0,,64,4321,0,none,47,gre,\
+            120,203.0.113.1,198.51.100.1,";
+        let input = format!("{}\n{}\n{}\nnot a log\n", UDP_LOG, gre, gre);
+
+        let mut report = UnknownProtoReport::default();
+        let logs = parse_logs_reporting(&input, &mut report);
+        assert_eq!(4, logs.len());
+        assert_eq!(Some(&2), report.protocols.get("gre"));
+        assert_eq!(1, report.protocols.len());
+    }
+
+    #[test]
+    fn parse_sample_takes_first_lines() {
+        let input = format!("{}\n\n{}\nnot a log\n", UDP_LOG, UDP_LOG);
+        let logs = parse_sample(&input, 2);
+        assert_eq!(2, logs.len());
+        assert!(logs.iter().all(|log| log.is_ok()));
+
+        assert_eq!(3, parse_sample(&input, 10).len());
+        assert!(parse_sample(&input, 0).is_empty());
+    }
+
+    #[test]
+    fn parse_concatenated_splits_records() {
+        let tcp = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,61633,0,DF,6,tcp,\
+            52,192.168.10.15,192.168.20.14,\
+            52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK";
+
+        let udp_15 = UDP_LOG.replacen("96", "15", 1);
+
+        //UDP entries end with a number, running into the next rule number
+        let input = format!("{}{}{}{}{}\n", tcp, UDP_LOG, udp_15, UDP_LOG, tcp);
+        let logs = parse_concatenated(&input);
+        assert_eq!(5, logs.len());
+        let logs: Vec<&FwLog> = logs.iter().map(|log| log.as_ref().unwrap()).collect();
+
+        let rules: Vec<u32> = logs
+            .iter()
+            .map(|log| log.packet_filter.rule_info.number)
+            .collect();
+        assert_eq!(vec![96, 96, 15, 96, 96], rules);
+        let protos: Vec<u8> = logs.iter().map(|log| log.protocol.num).collect();
+        assert_eq!(vec![6, 17, 17, 17, 6], protos);
+        for udp in &logs[1..4] {
+            assert_eq!(parse_log(UDP_LOG).unwrap().proto_info, udp.proto_info);
+        }
+        assert_eq!(Some((52461, 9100)), logs[4].ports());
+
+        let logs = parse_concatenated(&format!("not a log{}", tcp));
+        assert_eq!(1, logs.len());
+        assert!(logs[0].is_err());
+
+        assert!(parse_concatenated("").is_empty());
+    }
+
+    #[test]
+    fn parse_files_parallel_keeps_order() {
+        let dir = env::temp_dir().join(format!("senpa-batch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut paths = Vec::new();
+        for i in 0..5 {
+            let path = dir.join(format!("filter{}.log", i));
+            let content = format!("{}\nbroken line {}\n", UDP_LOG, i);
+            fs::write(&path, content).unwrap();
+            paths.push(path);
+        }
+        paths.push(dir.join("missing.log"));
+
+        let results = parse_files_parallel(&paths);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths.len(), results.len());
+        for (i, (path, logs)) in results.iter().take(5).enumerate() {
+            assert_eq!(&paths[i], path);
+            let logs = logs.as_ref().unwrap();
+            assert_eq!(2, logs.len());
+            assert!(logs[0].is_ok());
+            assert_eq!(
+                format!("broken line {}", i),
+                logs[1].as_ref().unwrap_err().raw_log
+            );
+        }
+        assert!(results[5].1.is_err());
+    }
+}
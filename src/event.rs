@@ -0,0 +1,90 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::log::FwLog;
+use crate::packet_filter::Action;
+use crate::protocol::ProtoName;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///A firewall event reduced to the fields most sinks need.
+///
+/// Sinks written against `Event` don't depend on the full [`FwLog`] shape,
+/// which grows as more of the log is parsed.
+///
+/// # Example
+/// ```rust
+/// use senpa::{event::Event, parse_log, Action, ProtoName};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+///
+/// let event = Event::from(log);
+/// assert_eq!(Action::Pass, event.action);
+/// assert_eq!(ProtoName::Udp, event.protocol);
+/// assert_eq!(Some(161), event.dst_port);
+/// assert_eq!("96", event.rule_id);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Event {
+    /// The time of the event since the Unix epoch, if the line carried one (see [`FwLog::timestamp`]).
+    /// Otherwise callers may fill it from the syslog header.
+    pub timestamp: Option<Duration>,
+    pub action: Action,
+    pub src: IpAddr,
+    pub src_port: Option<u16>,
+    pub dst: IpAddr,
+    pub dst_port: Option<u16>,
+    pub protocol: ProtoName,
+    /// The rule number, with its sub-rule when set (e.g. `96.1`).
+    pub rule_id: String,
+}
+
+impl From<FwLog> for Event {
+    fn from(log: FwLog) -> Self {
+        let ports = log.ports();
+        Event {
+            timestamp: log.timestamp,
+            rule_id: log.packet_filter.rule_info.rule_id(),
+            action: log.packet_filter.action,
+            src: log.ip_data.src,
+            src_port: ports.map(|(srcport, _)| srcport),
+            dst: log.ip_data.dst,
+            dst_port: ports.map(|(_, dstport)| dstport),
+            protocol: log.inner_protocol.unwrap_or(log.protocol).name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_log;
+
+    #[test]
+    fn event_from_icmp_log() {
+        let log = parse_log(
+            "97,,,02f4bab031b57d1e30553ce08e0ec131,em0,match,block,in,\
+            4,0x0,,64,12345,0,none,1,icmp,\
+            84,10.0.0.5,192.168.1.1,request,1234,1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Event {
+                timestamp: None,
+                action: Action::Block,
+                src: "10.0.0.5".parse().unwrap(),
+                src_port: None,
+                dst: "192.168.1.1".parse().unwrap(),
+                dst_port: None,
+                protocol: ProtoName::Icmp,
+                rule_id: "97".into(),
+            },
+            Event::from(log)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use crate::ip::IpSpecific;
+use crate::log::FwLog;
+use crate::protocol::ProtoInfo;
+
+struct FieldMap(BTreeMap<String, String>);
+
+impl FieldMap {
+    fn insert<V: ToString>(&mut self, key: &str, value: V) {
+        self.0.insert(key.into(), value.to_string());
+    }
+
+    fn insert_opt<V: ToString>(&mut self, key: &str, value: Option<V>) {
+        if let Some(value) = value {
+            self.insert(key, value);
+        }
+    }
+
+    ///Inserts the version-specific header columns, with `prefix` before each key.
+    fn insert_ip_specific(&mut self, prefix: &str, ip_specific: &IpSpecific) {
+        let key = |key: &str| format!("{}{}", prefix, key);
+        match ip_specific {
+            IpSpecific::IpV4(ipv4) => {
+                self.insert(&key("ip.version"), ipv4.version);
+                self.insert(&key("ipv4.tos"), format!("0x{:02x}", ipv4.tos));
+                self.insert_opt(&key("ipv4.ecn"), ipv4.ecn.as_ref());
+                self.insert(&key("ipv4.ttl"), ipv4.ttl);
+                self.insert(&key("ipv4.id"), ipv4.id);
+                self.insert(&key("ipv4.offset"), ipv4.offset);
+                self.insert(&key("ipv4.flags"), &ipv4.flags);
+            }
+            IpSpecific::Ipv6(ipv6) => {
+                self.insert(&key("ip.version"), 6);
+                self.insert(&key("ipv6.class"), format!("0x{:02x}", ipv6.traffic_class));
+                self.insert(&key("ipv6.flowlabel"), &ipv6.flow_label);
+                self.insert(&key("ipv6.hoplimit"), ipv6.hoplimit);
+                if !ipv6.ext_headers.is_empty() {
+                    let ext_headers: Vec<String> =
+                        ipv6.ext_headers.iter().map(u8::to_string).collect();
+                    self.insert(&key("ipv6.ext_headers"), ext_headers.join(","));
+                }
+            }
+        }
+    }
+}
+
+impl FwLog {
+    /// Flattens the log into a map of dotted keys (`rule.number`, `src.ip`, `tcp.flags`, ...) to values.
+    ///
+    /// Absent optional fields are omitted. Useful for templating and key-value sinks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use senpa::parse_log;
+    ///
+    /// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+    /// 4,0x0,,127,58940,0,none,17,udp,\
+    /// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+    ///
+    /// let fields = log.to_field_map();
+    /// assert_eq!("192.168.10.15", fields["src.ip"]);
+    /// assert_eq!("161", fields["dst.port"]);
+    /// ```
+    pub fn to_field_map(&self) -> BTreeMap<String, String> {
+        let mut map = FieldMap(BTreeMap::new());
+
+        let packet_filter = &self.packet_filter;
+        let rule_info = &packet_filter.rule_info;
+        map.insert("rule.number", rule_info.number);
+        map.insert_opt("rule.subrulenr", rule_info.subrulenr);
+        map.insert_opt("rule.anchorname", rule_info.anchorname.as_ref());
+        map.insert("rule.label", &rule_info.label);
+        map.insert_opt("rule.ridentifier", rule_info.ridentifier);
+        map.insert_opt("rule.tracker", rule_info.tracker);
+        map.insert("interface", &packet_filter.interface);
+        map.insert("reason", &packet_filter.reason);
+        map.insert("action", &packet_filter.action);
+        map.insert_opt("reject_method", packet_filter.reject_method);
+        map.insert("dir", &packet_filter.dir);
+
+        map.insert_ip_specific("", &self.ip_specific);
+        if let Some(inner) = &self.inner {
+            map.insert_ip_specific("inner.", inner);
+        }
+
+        map.insert("ip.length", self.ip_data.length);
+        map.insert("src.ip", self.ip_data.src);
+        map.insert("dst.ip", self.ip_data.dst);
+        map.insert("proto.num", self.protocol.num);
+        map.insert("proto.name", &self.protocol.name);
+        if let Some(inner_protocol) = &self.inner_protocol {
+            map.insert("inner.proto.num", inner_protocol.num);
+            map.insert("inner.proto.name", &inner_protocol.name);
+        }
+
+        if let Some((srcport, dstport)) = self.ports() {
+            map.insert("src.port", srcport);
+            map.insert("dst.port", dstport);
+        }
+
+        match &self.proto_info {
+            ProtoInfo::TcpInfo(tcp_info) => {
+                map.insert("tcp.datalen", tcp_info.data_len);
+                map.insert("tcp.flags", &tcp_info.flags);
+                map.insert("tcp.seq", &tcp_info.sequence_number);
+                map.insert_opt("tcp.ack", tcp_info.ack_number);
+                map.insert("tcp.window", tcp_info.window);
+                map.insert_opt("tcp.urg", tcp_info.urg);
+                map.insert("tcp.options", &tcp_info.options);
+            }
+            ProtoInfo::UdpInfo(udp_info) => {
+                map.insert("udp.datalen", udp_info.data_len);
+            }
+            ProtoInfo::IcmpInfo(icmp_info) => {
+                map.insert("icmp.type", &icmp_info.icmp_type);
+                map.insert_opt("icmp.type_num", icmp_info.type_num);
+                map.insert_opt("icmp.code", icmp_info.code);
+                map.insert_opt("icmp.id", icmp_info.id);
+                map.insert_opt("icmp.seq", icmp_info.seq);
+                if let Some(embedded) = &icmp_info.embedded {
+                    map.insert("icmp.embedded.dst", embedded.dst);
+                    map.insert_opt("icmp.embedded.proto", embedded.proto_num);
+                    map.insert_opt("icmp.embedded.port", embedded.port);
+                }
+                map.insert_opt("icmp.mtu", icmp_info.mtu);
+                map.insert_opt("icmp.details", icmp_info.details.as_ref());
+            }
+            ProtoInfo::VrrpInfo(vrrp_info) => {
+                map.insert_opt("vrrp.version", vrrp_info.version);
+                map.insert_opt("vrrp.vrid", vrrp_info.vrid);
+                map.insert_opt("vrrp.priority", vrrp_info.priority);
+            }
+            ProtoInfo::DccpInfo(dccp_info) => {
+                map.insert_opt("dccp.service_code", dccp_info.service_code);
+            }
+            ProtoInfo::PimInfo(pim_info) => {
+                map.insert_opt("pim.type", pim_info.pim_type.as_ref());
+            }
+            ProtoInfo::UnknownInfo(raw) => {
+                map.insert("proto.data", raw);
+            }
+        }
+
+        map.insert_opt(
+            "timestamp",
+            self.timestamp.map(|timestamp| timestamp.as_secs_f64()),
+        );
+        if self.truncated {
+            map.insert("truncated", true);
+        }
+
+        map.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_log;
+
+    #[test]
+    fn to_field_map_tcp() {
+        let log = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,61633,0,DF,6,tcp,\
+            52,192.168.10.15,192.168.20.14,\
+            52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK",
+        )
+        .unwrap();
+
+        let expected: BTreeMap<String, String> = [
+            ("action", "pass"),
+            ("dir", "out"),
+            ("dst.ip", "192.168.20.14"),
+            ("dst.port", "9100"),
+            ("interface", "vlan0.20"),
+            ("ip.length", "52"),
+            ("ip.version", "4"),
+            ("ipv4.flags", "DF"),
+            ("ipv4.id", "61633"),
+            ("ipv4.offset", "0"),
+            ("ipv4.tos", "0x00"),
+            ("ipv4.ttl", "127"),
+            ("proto.name", "tcp"),
+            ("proto.num", "6"),
+            ("reason", "match"),
+            ("rule.label", "fae559338f65e11c53669fc3642c93c2"),
+            ("rule.number", "96"),
+            ("src.ip", "192.168.10.15"),
+            ("src.port", "52461"),
+            ("tcp.datalen", "0"),
+            ("tcp.flags", "S"),
+            ("tcp.options", "mss;nop;wscale;nop;nop;sackOK"),
+            ("tcp.seq", "3442468761"),
+            ("tcp.window", "64240"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+        assert_eq!(expected, log.to_field_map());
+    }
+
+    #[test]
+    fn to_field_map_udp() {
+        let log = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,58940,0,none,17,udp,\
+            106,192.168.10.15,192.168.20.11,49678,161,86",
+        )
+        .unwrap();
+
+        let fields = log.to_field_map();
+        assert_eq!("86", fields["udp.datalen"]);
+        assert_eq!("49678", fields["src.port"]);
+        assert!(!fields.contains_key("tcp.flags"));
+        assert!(!fields.contains_key("rule.subrulenr"));
+    }
+
+    #[test]
+    fn to_field_map_tunnel() {
+        let log = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,58940,0,none,4,ipencap,\
+            126,203.0.113.1,198.51.100.1,\
+            4,0x0,,64,1,0,none,17,udp,\
+            49678,161,86",
+        )
+        .unwrap();
+
+        let fields = log.to_field_map();
+        assert_eq!("4", fields["proto.num"]);
+        assert_eq!("17", fields["inner.proto.num"]);
+        assert_eq!("udp", fields["inner.proto.name"]);
+        assert_eq!("64", fields["inner.ipv4.ttl"]);
+        assert_eq!("127", fields["ipv4.ttl"]);
+        assert_eq!("86", fields["udp.datalen"]);
+    }
+}
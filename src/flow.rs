@@ -0,0 +1,314 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::ip::IpSpecific;
+use crate::log::FwLog;
+use crate::protocol::{ProtoInfo, TcpFlags};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///The addresses, ports and protocol number identifying a flow.
+///
+/// Ports are `None` for protocols without them (e.g. ICMP).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FiveTuple {
+    pub src: IpAddr,
+    pub dst: IpAddr,
+    pub src_port: Option<u16>,
+    pub dst_port: Option<u16>,
+    pub proto: u8,
+}
+
+///A flow tuple with its endpoints in canonical order, as returned by [`FwLog::canonical_tuple`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CanonicalTuple {
+    /// The tuple with its endpoints ordered by (address, port).
+    pub tuple: FiveTuple,
+    /// `true` if the logged source is the canonical destination.
+    pub swapped: bool,
+}
+
+///The fields of a NetFlow v5 flow record, for export to legacy collectors.
+///
+/// Fields the log doesn't carry (next hop, SNMP interface indexes, AS numbers, masks, timestamps)
+/// are left to the exporter and not represented.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NetflowV5Record {
+    pub src_addr: Ipv4Addr,
+    pub dst_addr: Ipv4Addr,
+    /// SNMP index of the input interface, always 0.
+    pub input: u16,
+    /// SNMP index of the output interface, always 0.
+    pub output: u16,
+    /// Packets in the flow, 1 as every log is a single packet.
+    pub packets: u32,
+    /// Bytes in the flow, the logged IP length.
+    pub octets: u32,
+    /// Source port, 0 for protocols without ports.
+    pub src_port: u16,
+    /// Destination port, 0 for protocols without ports.
+    pub dst_port: u16,
+    /// TCP flags in their header bit positions, 0 for other protocols.
+    pub tcp_flags: u8,
+    /// IANA protocol number.
+    pub prot: u8,
+    pub tos: u8,
+}
+
+fn tcp_flags_bits(flags: &TcpFlags) -> u8 {
+    [
+        flags.fin, flags.syn, flags.rst, flags.psh, flags.ack, flags.urg, flags.ece, flags.cwr,
+    ]
+    .iter()
+    .enumerate()
+    .filter(|(_, set)| **set)
+    .fold(0, |bits, (bit, _)| bits | 1 << bit)
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn hash_addr(hash: u64, addr: &IpAddr) -> u64 {
+    match addr {
+        IpAddr::V4(v4) => fnv1a(fnv1a(hash, &[4]), &v4.octets()),
+        IpAddr::V6(v6) => fnv1a(fnv1a(hash, &[6]), &v6.octets()),
+    }
+}
+
+fn hash_port(hash: u64, port: Option<u16>) -> u64 {
+    match port {
+        Some(port) => fnv1a(fnv1a(hash, &[1]), &port.to_be_bytes()),
+        None => fnv1a(hash, &[0]),
+    }
+}
+
+impl FiveTuple {
+    /// Returns the tuple with its endpoints ordered by (address, port),
+    /// so both directions of a connection are equal.
+    pub fn normalized(&self) -> FiveTuple {
+        if (self.src, self.src_port) <= (self.dst, self.dst_port) {
+            self.clone()
+        } else {
+            FiveTuple {
+                src: self.dst,
+                dst: self.src,
+                src_port: self.dst_port,
+                dst_port: self.src_port,
+                proto: self.proto,
+            }
+        }
+    }
+
+    /// Hashes the tuple with FNV-1a.
+    ///
+    /// Unlike `std::hash::Hash` with the default hasher, the result is the same
+    /// across runs, processes and Rust versions, so it can be stored.
+    pub fn stable_hash(&self) -> u64 {
+        let hash = hash_addr(FNV_OFFSET_BASIS, &self.src);
+        let hash = hash_addr(hash, &self.dst);
+        let hash = hash_port(hash, self.src_port);
+        let hash = hash_port(hash, self.dst_port);
+        fnv1a(hash, &[self.proto])
+    }
+}
+
+impl FwLog {
+    /// Returns the addresses, ports and protocol number of the logged packet,
+    /// the encapsulated protocol for tunnels, see [`FwLog::payload_protocol`].
+    pub fn five_tuple(&self) -> FiveTuple {
+        let ports = self.ports();
+        FiveTuple {
+            src: self.ip_data.src,
+            dst: self.ip_data.dst,
+            src_port: ports.map(|(srcport, _)| srcport),
+            dst_port: ports.map(|(_, dstport)| dstport),
+            proto: self.payload_protocol().num,
+        }
+    }
+
+    /// Returns the flow tuple with its endpoints ordered by (address, port), regardless of [`Dir`](crate::Dir),
+    /// so a request and its reply map to the same tuple.
+    pub fn canonical_tuple(&self) -> CanonicalTuple {
+        let tuple = self.five_tuple();
+        let swapped = (tuple.src, tuple.src_port) > (tuple.dst, tuple.dst_port);
+        CanonicalTuple {
+            tuple: tuple.normalized(),
+            swapped,
+        }
+    }
+
+    /// Converts the log into a NetFlow v5 record, `None` for IPv6 packets as v5 only carries IPv4.
+    pub fn to_netflow_v5_fields(&self) -> Option<NetflowV5Record> {
+        let IpSpecific::IpV4(ipv4) = &self.ip_specific else {
+            return None;
+        };
+        let (IpAddr::V4(src_addr), IpAddr::V4(dst_addr)) = (self.ip_data.src, self.ip_data.dst)
+        else {
+            return None;
+        };
+        let (src_port, dst_port) = self.ports().unwrap_or((0, 0));
+        let tcp_flags = match &self.proto_info {
+            ProtoInfo::TcpInfo(tcp_info) => tcp_flags_bits(&tcp_info.tcp_flags()),
+            _ => 0,
+        };
+
+        Some(NetflowV5Record {
+            src_addr,
+            dst_addr,
+            input: 0,
+            output: 0,
+            packets: 1,
+            octets: self.byte_count(),
+            src_port,
+            dst_port,
+            tcp_flags,
+            prot: self.payload_protocol().num,
+            tos: ipv4.tos,
+        })
+    }
+
+    /// Returns a stable hash of the flow, equal for both directions of a connection.
+    ///
+    /// # Example
+    /// ```rust
+    /// use senpa::parse_log;
+    ///
+    /// let request = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+    /// 4,0x0,,127,58940,0,none,17,udp,\
+    /// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+    /// let reply = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,in,\
+    /// 4,0x0,,64,1234,0,none,17,udp,\
+    /// 120,192.168.20.11,192.168.10.15,161,49678,100").unwrap();
+    ///
+    /// assert_eq!(request.flow_hash(), reply.flow_hash());
+    /// ```
+    pub fn flow_hash(&self) -> u64 {
+        self.five_tuple().normalized().stable_hash()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_log;
+
+    const UDP_LOG: &str = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+
+    fn tuple(src: &str, src_port: u16, dst: &str, dst_port: u16) -> FiveTuple {
+        FiveTuple {
+            src: src.parse().unwrap(),
+            dst: dst.parse().unwrap(),
+            src_port: Some(src_port),
+            dst_port: Some(dst_port),
+            proto: 17,
+        }
+    }
+
+    #[test]
+    fn five_tuple_test() {
+        let log = parse_log(UDP_LOG).unwrap();
+        assert_eq!(
+            tuple("192.168.10.15", 49678, "192.168.20.11", 161),
+            log.five_tuple()
+        );
+    }
+
+    #[test]
+    fn normalized_test() {
+        let forward = tuple("192.168.10.15", 49678, "192.168.20.11", 161);
+        let backward = tuple("192.168.20.11", 161, "192.168.10.15", 49678);
+        assert_eq!(forward, forward.normalized());
+        assert_eq!(forward, backward.normalized());
+
+        //Same address, ordered by port
+        let same_host = tuple("10.0.0.1", 8080, "10.0.0.1", 80);
+        assert_eq!(Some(80), same_host.normalized().src_port);
+    }
+
+    #[test]
+    fn netflow_v5_test() {
+        let log = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x10,,127,61633,0,DF,6,tcp,\
+            52,192.168.10.15,192.168.20.14,\
+            52461,9100,0,SA,3442468761,1,64240,,mss",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(NetflowV5Record {
+                src_addr: Ipv4Addr::new(192, 168, 10, 15),
+                dst_addr: Ipv4Addr::new(192, 168, 20, 14),
+                input: 0,
+                output: 0,
+                packets: 1,
+                octets: 52,
+                src_port: 52461,
+                dst_port: 9100,
+                tcp_flags: 0x12,
+                prot: 6,
+                tos: 0x10,
+            }),
+            log.to_netflow_v5_fields()
+        );
+
+        let udp = parse_log(UDP_LOG).unwrap().to_netflow_v5_fields().unwrap();
+        assert_eq!((49678, 161, 0), (udp.src_port, udp.dst_port, udp.tcp_flags));
+
+        let v6 = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            6,0x00,,64,udp,17,\
+            60,2001:db8::1,2001:db8::2,49678,161,52",
+        )
+        .unwrap();
+        assert_eq!(None, v6.to_netflow_v5_fields());
+    }
+
+    #[test]
+    fn canonical_tuple_test() {
+        let request = parse_log(UDP_LOG).unwrap();
+        let reply = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,in,\
+            4,0x0,,64,1234,0,none,17,udp,\
+            120,192.168.20.11,192.168.10.15,161,49678,100",
+        )
+        .unwrap();
+
+        let canonical = request.canonical_tuple();
+        assert!(!canonical.swapped);
+        assert_eq!(request.five_tuple(), canonical.tuple);
+
+        let canonical_reply = reply.canonical_tuple();
+        assert!(canonical_reply.swapped);
+        assert_eq!(canonical.tuple, canonical_reply.tuple);
+    }
+
+    #[test]
+    fn stable_hash_test() {
+        let forward = tuple("192.168.10.15", 49678, "192.168.20.11", 161);
+        let backward = tuple("192.168.20.11", 161, "192.168.10.15", 49678);
+
+        //Pinned value: the hash must not change between releases
+        assert_eq!(0xe1740ae9ad139977, forward.stable_hash());
+        assert_ne!(forward.stable_hash(), backward.stable_hash());
+        assert_eq!(
+            forward.normalized().stable_hash(),
+            backward.normalized().stable_hash()
+        );
+
+        let mut other_proto = forward.clone();
+        other_proto.proto = 6;
+        assert_ne!(forward.stable_hash(), other_proto.stable_hash());
+    }
+}
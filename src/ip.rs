@@ -1,19 +1,19 @@
 use std::{
+    fmt::Display,
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
     str::FromStr,
 };
 
 use crate::{
     protocol::{ProtoName, Protocol},
-    utils::{self, csv, hexadecimal_value},
+    utils::{csv, hex_or_decimal_u16, opt_field, tos_value},
 };
 
 use nom::character::complete::char;
 use nom::character::complete::{u16 as parse_u16, u8 as parse_u8};
 use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_till},
-    character::complete::alphanumeric1,
+    bytes::complete::{take_till, take_while1},
+    character::complete::{alphanumeric0, alphanumeric1},
     combinator::{fail, opt},
     sequence::separated_pair,
     IResult, Parser,
@@ -40,6 +40,15 @@ fn parse_ipv6_addr(input: &str) -> IResult<&str, Ipv6Addr> {
     }
 }
 
+pub(crate) fn parse_ip_addr(input: &str) -> IResult<&str, IpAddr> {
+    let (next, addr) = take_till(|c| c == ',')(input)?;
+
+    match IpAddr::from_str(addr) {
+        Ok(addr) => Ok((next, addr)),
+        Err(_) => fail(input),
+    }
+}
+
 fn parse_src_dst_addr<'a>(
     input: &'a str,
     specific: &IpSpecific,
@@ -62,13 +71,85 @@ fn parse_src_dst_addr<'a>(
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-
 pub struct IpData {
     pub length: u16,
     pub src: IpAddr,
     pub dst: IpAddr,
 }
 
+fn is_private(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => v4.is_private(),
+        IpAddr::V6(v6) => v6.is_unique_local(),
+    }
+}
+
+fn is_link_local(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => v4.is_link_local(),
+        IpAddr::V6(v6) => v6.is_unicast_link_local(),
+    }
+}
+
+fn as_v4_mapped(addr: &IpAddr) -> Option<Ipv4Addr> {
+    match addr {
+        IpAddr::V4(_) => None,
+        IpAddr::V6(v6) => v6.to_ipv4_mapped(),
+    }
+}
+
+impl IpData {
+    /// Returns the IPv4 address embedded in an IPv4-mapped IPv6 source (`::ffff:a.b.c.d`).
+    pub fn src_as_v4_mapped(&self) -> Option<Ipv4Addr> {
+        as_v4_mapped(&self.src)
+    }
+
+    /// Returns the IPv4 address embedded in an IPv4-mapped IPv6 destination (`::ffff:a.b.c.d`).
+    pub fn dst_as_v4_mapped(&self) -> Option<Ipv4Addr> {
+        as_v4_mapped(&self.dst)
+    }
+
+    /// Returns `true` if the source is a private address (RFC 1918 for IPv4, unique local for IPv6).
+    pub fn src_is_private(&self) -> bool {
+        is_private(&self.src)
+    }
+
+    /// Returns `true` if the destination is a private address (RFC 1918 for IPv4, unique local for IPv6).
+    pub fn dst_is_private(&self) -> bool {
+        is_private(&self.dst)
+    }
+
+    /// Returns `true` if the source is a link-local address.
+    pub fn src_is_link_local(&self) -> bool {
+        is_link_local(&self.src)
+    }
+
+    /// Returns `true` if the destination is a link-local address.
+    pub fn dst_is_link_local(&self) -> bool {
+        is_link_local(&self.dst)
+    }
+
+    /// Returns `true` if the source is a multicast address.
+    pub fn src_is_multicast(&self) -> bool {
+        self.src.is_multicast()
+    }
+
+    /// Returns `true` if the destination is a multicast address.
+    pub fn dst_is_multicast(&self) -> bool {
+        self.dst.is_multicast()
+    }
+
+    /// Returns `true` if the source is a loopback address.
+    pub fn src_is_loopback(&self) -> bool {
+        self.src.is_loopback()
+    }
+
+    /// Returns `true` if the destination is a loopback address.
+    pub fn dst_is_loopback(&self) -> bool {
+        self.dst.is_loopback()
+    }
+}
+
 pub(crate) fn parse_ip_data<'a>(input: &'a str, specific: &IpSpecific) -> IResult<&'a str, IpData> {
     let (next, length) = csv(parse_u16)(input)?;
     let (next, (src, dst)) = parse_src_dst_addr(next, specific)?;
@@ -83,10 +164,67 @@ pub enum IpSpecific {
     Ipv6(IpV6),
 }
 
+impl Display for IpSpecific {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpSpecific::IpV4(ipv4) => {
+                write!(f, "IPv4 ttl={} id={}", ipv4.ttl, ipv4.id)?;
+                if ipv4.offset != 0 {
+                    write!(f, " off={}", ipv4.offset)?;
+                }
+                //pf logs "none" when no flag is set
+                match ipv4.flags.as_str() {
+                    "none" => Ok(()),
+                    flags => write!(f, " {}", flags),
+                }
+            }
+            IpSpecific::Ipv6(ipv6) => write!(
+                f,
+                "IPv6 hlim={} class=0x{:02x}",
+                ipv6.hoplimit, ipv6.traffic_class
+            ),
+        }
+    }
+}
+
+impl IpSpecific {
+    /// Returns the IPv4 TTL or the IPv6 hop limit.
+    pub fn hop_limit(&self) -> u8 {
+        match self {
+            IpSpecific::IpV4(ipv4) => ipv4.ttl,
+            IpSpecific::Ipv6(ipv6) => ipv6.hoplimit,
+        }
+    }
+
+    /// Formats the version-specific header columns as they appear in the log,
+    /// e.g. `4,0x0,,127,61633,0,DF` for IPv4 or `6,0x00,,64` for IPv6.
+    ///
+    /// The protocol columns that follow them are not included.
+    pub fn to_log_fields(&self) -> String {
+        match self {
+            IpSpecific::IpV4(ipv4) => format!(
+                "{},0x{:x},{},{},{},{},{}",
+                ipv4.version,
+                ipv4.tos,
+                ipv4.ecn.as_deref().unwrap_or_default(),
+                ipv4.ttl,
+                ipv4.id,
+                ipv4.offset,
+                ipv4.flags
+            ),
+            IpSpecific::Ipv6(ipv6) => format!(
+                "6,0x{:02x},{},{}",
+                ipv6.traffic_class, ipv6.flow_label, ipv6.hoplimit
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IpV4 {
     pub version: u8,
+    #[cfg_attr(feature = "serde-hex", serde(with = "crate::serde_hex"))]
     pub tos: u8,
     pub ecn: Option<String>,
     pub ttl: u8,
@@ -95,13 +233,155 @@ pub struct IpV4 {
     pub flags: String,
 }
 
-fn parse_ipv4_header(input: &str) -> IResult<&str, (Protocol, IpSpecific)> {
-    let (next, tos) = csv(utils::hexadecimal_value)(input)?;
-    let (next, ecn) = csv(opt(alphanumeric1.map(|s: &str| s.into())))(next)?;
+///The per-hop behavior named by a DSCP value, as returned by [`IpV4::dscp_class`] and [`IpV6::dscp_class`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DscpClass {
+    Default,
+    Cs1,
+    Cs2,
+    Cs3,
+    Cs4,
+    Cs5,
+    Cs6,
+    Cs7,
+    Af11,
+    Af12,
+    Af13,
+    Af21,
+    Af22,
+    Af23,
+    Af31,
+    Af32,
+    Af33,
+    Af41,
+    Af42,
+    Af43,
+    Ef,
+    /// A DSCP value without a standard name.
+    Other(u8),
+}
+
+///Standard DSCP code points and their per-hop behaviors.
+const DSCP_CLASSES: [(u8, DscpClass); 21] = [
+    (0, DscpClass::Default),
+    (8, DscpClass::Cs1),
+    (10, DscpClass::Af11),
+    (12, DscpClass::Af12),
+    (14, DscpClass::Af13),
+    (16, DscpClass::Cs2),
+    (18, DscpClass::Af21),
+    (20, DscpClass::Af22),
+    (22, DscpClass::Af23),
+    (24, DscpClass::Cs3),
+    (26, DscpClass::Af31),
+    (28, DscpClass::Af32),
+    (30, DscpClass::Af33),
+    (32, DscpClass::Cs4),
+    (34, DscpClass::Af41),
+    (36, DscpClass::Af42),
+    (38, DscpClass::Af43),
+    (40, DscpClass::Cs5),
+    (46, DscpClass::Ef),
+    (48, DscpClass::Cs6),
+    (56, DscpClass::Cs7),
+];
+
+impl DscpClass {
+    /// Returns the class of a 6-bit DSCP value, [`DscpClass::Other`] when it has no standard name.
+    pub fn from_dscp(dscp: u8) -> Self {
+        DSCP_CLASSES
+            .iter()
+            .find(|(code, _)| *code == dscp)
+            .map_or(DscpClass::Other(dscp), |(_, class)| *class)
+    }
+}
+
+impl IpV4 {
+    /// Returns the 6-bit DSCP value, the upper bits of the TOS byte.
+    pub fn dscp(&self) -> u8 {
+        self.tos >> 2
+    }
+
+    /// Returns the per-hop behavior named by the DSCP value.
+    pub fn dscp_class(&self) -> DscpClass {
+        DscpClass::from_dscp(self.dscp())
+    }
+
+    /// Returns `true` if the packet is a fragment: the offset is non-zero or the `MF` flag is set.
+    pub fn is_fragment(&self) -> bool {
+        self.offset != 0 || self.flags.contains("MF")
+    }
+}
+
+/// The bits of the IPv4 fragment offset field holding the offset itself.
+pub const FRAG_OFFSET_MASK: u16 = 0x1fff;
+
+///Flag bits some pf versions log inline, in the high bits of the offset field.
+const FRAG_FLAG_BITS: [(u16, &str); 3] = [(0x8000, "RF"), (0x4000, "DF"), (0x2000, "MF")];
+
+/// Splits a raw offset field into its 13-bit offset and the names of the flag
+/// bits set above it, joined by `+` (`"none"` when no flag bit is set).
+///
+/// # Example
+/// ```rust
+/// use senpa::ip::split_frag_offset;
+///
+/// assert_eq!((185, "none".to_string()), split_frag_offset(185));
+/// assert_eq!((185, "DF+MF".to_string()), split_frag_offset(0x6000 | 185));
+/// ```
+pub fn split_frag_offset(raw: u16) -> (u16, String) {
+    let flags: Vec<&str> = FRAG_FLAG_BITS
+        .iter()
+        .filter(|(bit, _)| raw & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+
+    let flags = match flags.is_empty() {
+        true => "none".into(),
+        false => flags.join("+"),
+    };
+    (raw & FRAG_OFFSET_MASK, flags)
+}
+
+///Merges two `+` joined flag lists, known flags first in bit order, then any other name of `column`.
+fn merge_frag_flags(column: &str, inline: &str) -> String {
+    let is_set = |flag: &str| {
+        column
+            .split('+')
+            .chain(inline.split('+'))
+            .any(|name| name == flag)
+    };
+    let is_known = |name: &str| FRAG_FLAG_BITS.iter().any(|(_, flag)| *flag == name);
+
+    let mut flags: Vec<&str> = FRAG_FLAG_BITS
+        .iter()
+        .map(|(_, flag)| *flag)
+        .filter(|flag| is_set(flag))
+        .collect();
+    flags.extend(
+        column
+            .split('+')
+            .filter(|name| *name != "none" && !is_known(name)),
+    );
+
+    match flags.is_empty() {
+        true => "none".into(),
+        false => flags.join("+"),
+    }
+}
+
+fn parse_ipv4_header(input: &str, version: u8) -> IResult<&str, (Protocol, IpSpecific)> {
+    let (next, tos) = csv(tos_value)(input)?;
+    let (next, ecn) = csv(opt_field(alphanumeric1.map(|s: &str| s.into())))(next)?;
     let (next, ttl) = csv(parse_u8)(next)?;
-    let (next, id) = csv(parse_u16)(next)?;
+    let (next, id) = csv(hex_or_decimal_u16)(next)?;
     let (next, offset) = csv(parse_u16)(next)?;
-    let (next, flags) = csv(alphanumeric1).map(|s: &str| s.into()).parse(next)?;
+    let (next, flags) = csv(take_while1(|c: char| c.is_alphanumeric() || c == '+'))(next)?;
+
+    //Flags logged inline in the offset field are merged with the flags column
+    let (offset, inline_flags) = split_frag_offset(offset);
+    let flags = merge_frag_flags(flags, &inline_flags);
     let (next, protonum) = csv(parse_u8)(next)?;
     let (next, protoname) = csv(alphanumeric1).map(|s: &str| s).parse(next)?;
 
@@ -111,7 +391,7 @@ fn parse_ipv4_header(input: &str) -> IResult<&str, (Protocol, IpSpecific)> {
     };
 
     let ipv4 = IpV4 {
-        version: 4,
+        version,
         tos,
         ecn,
         ttl,
@@ -125,45 +405,132 @@ fn parse_ipv4_header(input: &str) -> IResult<&str, (Protocol, IpSpecific)> {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-
 pub struct IpV6 {
+    #[cfg_attr(feature = "serde-hex", serde(with = "crate::serde_hex"))]
     pub traffic_class: u8,
     pub flow_label: String,
     pub hoplimit: u8,
+    /// The extension headers logged before the transport protocol (hop-by-hop, routing, ...),
+    /// as their next-header values in order. Empty when the packet carries none.
+    pub ext_headers: Vec<u8>,
 }
 
-fn parse_ipv6_header(input: &str) -> IResult<&str, (Protocol, IpSpecific)> {
-    let (next, traffic_class) = csv(hexadecimal_value)(input)?;
-    let (next, flow_label) = csv(alphanumeric1).map(|s: &str| s.into()).parse(next)?;
+///Next-header values of the IPv6 extension headers, which are followed by another header.
+const IPV6_EXT_HEADERS: [u8; 6] = [0, 43, 44, 51, 60, 135];
+
+/// The next-header value of the IPv6 fragment header.
+pub const IPV6_FRAGMENT: u8 = 44;
+
+/// The largest IPv6 flow label, which is 20 bits wide.
+pub const MAX_FLOW_LABEL: u32 = 0xfffff;
+
+impl IpV6 {
+    /// Returns the 6-bit DSCP value, the upper bits of the traffic class.
+    pub fn dscp(&self) -> u8 {
+        self.traffic_class >> 2
+    }
+
+    /// Returns the per-hop behavior named by the DSCP value.
+    pub fn dscp_class(&self) -> DscpClass {
+        DscpClass::from_dscp(self.dscp())
+    }
+
+    /// Returns the raw numeric flow label, read as hexadecimal with an optional `0x` prefix.
+    ///
+    /// `None` when the label is blank or not a number.
+    pub fn flow_label_raw(&self) -> Option<u32> {
+        let digits = self
+            .flow_label
+            .strip_prefix("0x")
+            .unwrap_or(&self.flow_label);
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    /// Returns the numeric flow label masked to its 20 bits, see [`IpV6::flow_label_raw`].
+    pub fn flow_label_value(&self) -> Option<u32> {
+        self.flow_label_raw().map(|label| label & MAX_FLOW_LABEL)
+    }
+}
+
+fn parse_ipv6_header(input: &str, version: u8) -> IResult<&str, (Protocol, IpSpecific)> {
+    //IPv6 headers don't store the version, parse_ip_header only dispatches 6 here
+    debug_assert_eq!(6, version);
+
+    let (next, traffic_class) = csv(tos_value)(input)?;
+    //The flow label is commonly logged blank
+    let (next, flow_label) = csv(alphanumeric0).map(|s: &str| s.into()).parse(next)?;
     let (next, hoplimit) = csv(parse_u8)(next)?;
 
+    let (mut next, mut proto) = parse_ipv6_protocol(next)?;
+
+    //Extension headers are logged as further name,number pairs up to the transport protocol
+    let mut ext_headers = Vec::new();
+    while IPV6_EXT_HEADERS.contains(&proto.num) {
+        match parse_ipv6_protocol(next) {
+            Ok((rest, next_proto)) => {
+                ext_headers.push(proto.num);
+                (next, proto) = (rest, next_proto);
+            }
+            Err(_) => break,
+        }
+    }
+
     let ipv6 = IpV6 {
         traffic_class,
         flow_label,
         hoplimit,
+        ext_headers,
     };
 
-    let (next, protoname) = csv(take_till(|c| c == ','))(next)?;
+    Ok((next, (proto, IpSpecific::Ipv6(ipv6))))
+}
 
+fn parse_ipv6_protocol(input: &str) -> IResult<&str, Protocol> {
+    let (next, protoname) = csv(take_till(|c| c == ','))(input)?;
     let (next, protonum) = csv(parse_u8)(next)?;
 
-    let proto = Protocol {
-        name: ProtoName::from_str(protoname).unwrap(),
-        num: protonum,
+    //Some logs omit the textual name, the number still identifies the protocol
+    let name = match protoname {
+        "" => ProtoName::from_num(protonum),
+        protoname => ProtoName::from_str(protoname).unwrap(),
     };
 
-    Ok((next, (proto, IpSpecific::Ipv6(ipv6))))
+    Ok((
+        next,
+        Protocol {
+            name,
+            num: protonum,
+        },
+    ))
 }
 
 pub(crate) fn parse_ip_header(input: &str) -> IResult<&str, (Protocol, IpSpecific)> {
-    let (next, version) = csv(alt((tag("4"), tag("6"))))(input)?;
+    let (next, version) = csv(parse_u8)(input)?;
 
     match version {
-        "4" => parse_ipv4_header(next),
-        "6" => parse_ipv6_header(next),
-        _ => fail(next),
+        4 => parse_ipv4_header(next, version),
+        6 => parse_ipv6_header(next, version),
+        _ => fail(input),
+    }
+}
+/// IANA protocol number for IP-in-IP encapsulation.
+const IPPROTO_IPIP: u8 = 4;
+/// IANA protocol number for IPv6 encapsulated in IPv4 (6in4).
+const IPPROTO_IPV6: u8 = 41;
+
+/// Parses the header of an encapsulated packet when the outer protocol is a tunnel (IP-in-IP or 6in4).
+///
+/// The inner header is optional: tunnel logs that don't carry it leave the input untouched.
+pub(crate) fn parse_inner_ip_header<'a>(
+    input: &'a str,
+    outer: &Protocol,
+) -> IResult<&'a str, Option<(Protocol, IpSpecific)>> {
+    match outer.num {
+        IPPROTO_IPIP | IPPROTO_IPV6 => opt(parse_ip_header)(input),
+        _ => Ok((input, None)),
     }
 }
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -200,6 +567,60 @@ mod test {
         assert!(parse_ipv6_addr("2001:0kb8:85a3:0000:0000:8a2e:0370:7334").is_err())
     }
 
+    #[test]
+    fn ip_data_classification() {
+        let v4 = IpData {
+            length: 52,
+            src: "192.168.10.15".parse().unwrap(),
+            dst: "224.0.0.251".parse().unwrap(),
+        };
+        assert!(v4.src_is_private());
+        assert!(!v4.dst_is_private());
+        assert!(!v4.src_is_multicast());
+        assert!(v4.dst_is_multicast());
+        assert!(!v4.src_is_loopback());
+        assert!(!v4.src_is_link_local());
+
+        let v6 = IpData {
+            length: 52,
+            src: "fd00::1".parse().unwrap(),
+            dst: "::1".parse().unwrap(),
+        };
+        assert!(v6.src_is_private());
+        assert!(!v6.dst_is_private());
+        assert!(v6.dst_is_loopback());
+        assert!(!v6.src_is_loopback());
+
+        let link_local = IpData {
+            length: 52,
+            src: "169.254.1.1".parse().unwrap(),
+            dst: "fe80::1".parse().unwrap(),
+        };
+        assert!(link_local.src_is_link_local());
+        assert!(link_local.dst_is_link_local());
+    }
+
+    #[test]
+    fn ip_data_v4_mapped() {
+        let mapped = IpData {
+            length: 60,
+            src: "::ffff:192.168.1.1".parse().unwrap(),
+            dst: "2001:db8::1".parse().unwrap(),
+        };
+        assert_eq!(
+            Some(Ipv4Addr::new(192, 168, 1, 1)),
+            mapped.src_as_v4_mapped()
+        );
+        assert_eq!(None, mapped.dst_as_v4_mapped());
+
+        let v4 = IpData {
+            length: 60,
+            src: "192.168.1.1".parse().unwrap(),
+            dst: "192.168.1.2".parse().unwrap(),
+        };
+        assert_eq!(None, v4.src_as_v4_mapped());
+    }
+
     #[test]
     fn parse_ip_header_test() {
         let ipv4_header = "4,0x0,,127,58940,0,none,17,udp,\
@@ -229,4 +650,265 @@ mod test {
             parse_ip_header(ipv4_header)
         );
     }
+
+    #[test]
+    fn parse_ipv6_header_test() {
+        let expected = |flow_label: &str| {
+            Ok((
+                "60,2001:db8::1,2001:db8::2,",
+                (
+                    Protocol {
+                        name: ProtoName::Tcp,
+                        num: 6,
+                    },
+                    IpSpecific::Ipv6(IpV6 {
+                        traffic_class: 0,
+                        flow_label: flow_label.into(),
+                        hoplimit: 64,
+                        ext_headers: Vec::new(),
+                    }),
+                ),
+            ))
+        };
+
+        assert_eq!(
+            expected("abc12"),
+            parse_ip_header("6,0x00,abc12,64,tcp,6,60,2001:db8::1,2001:db8::2,")
+        );
+        assert_eq!(
+            expected(""),
+            parse_ip_header("6,0x00,,64,tcp,6,60,2001:db8::1,2001:db8::2,")
+        );
+    }
+
+    #[test]
+    fn is_fragment_test() {
+        let header = |offset: u16, flags: &str| IpV4 {
+            version: 4,
+            tos: 0,
+            ecn: None,
+            ttl: 64,
+            id: 1,
+            offset,
+            flags: flags.into(),
+        };
+
+        assert!(!header(0, "none").is_fragment());
+        assert!(!header(0, "DF").is_fragment());
+        assert!(header(0, "MF").is_fragment());
+        assert!(header(185, "none").is_fragment());
+    }
+
+    #[test]
+    fn parse_ipv4_header_inline_flags() {
+        //0x4000 | 185: DF set in the offset field
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,64,1,16569,none,17,udp,").unwrap();
+        let IpSpecific::IpV4(ipv4) = ipv4 else {
+            panic!("expected an IPv4 header");
+        };
+        assert_eq!(185, ipv4.offset);
+        assert_eq!("DF", ipv4.flags);
+
+        //0x2000 | 0x4000: both flags, no offset
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,64,1,24576,none,17,udp,").unwrap();
+        assert_eq!("4,0x0,,64,1,0,DF+MF", ipv4.to_log_fields());
+
+        //The inline bits are merged with the flags column
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,64,1,8377,MF,17,udp,").unwrap();
+        assert_eq!("4,0x0,,64,1,185,MF", ipv4.to_log_fields());
+
+        //0x2000: MF set in the offset field, DF in the flags column
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,64,1,8192,DF,17,udp,").unwrap();
+        let IpSpecific::IpV4(ipv4) = ipv4 else {
+            panic!("expected an IPv4 header");
+        };
+        assert_eq!(0, ipv4.offset);
+        assert_eq!("DF+MF", ipv4.flags);
+        assert!(ipv4.is_fragment());
+    }
+
+    #[test]
+    fn parse_ip_header_decimal_tos() {
+        let (_, (_, ipv4)) = parse_ip_header("4,47,,64,1,0,none,17,udp,").unwrap();
+        assert_eq!("4,0x2f,,64,1,0,none", ipv4.to_log_fields());
+
+        let (_, (_, ipv6)) = parse_ip_header("6,0,,64,tcp,6,").unwrap();
+        assert_eq!("6,0x00,,64", ipv6.to_log_fields());
+    }
+
+    #[test]
+    fn dscp_class_test() {
+        //0xb8 is EF (46) with no ECN bits
+        let (_, (_, ipv4)) = parse_ip_header("4,0xb8,,64,1,0,none,17,udp,").unwrap();
+        let IpSpecific::IpV4(ipv4) = ipv4 else {
+            panic!("expected an IPv4 header");
+        };
+        assert_eq!(46, ipv4.dscp());
+        assert_eq!(DscpClass::Ef, ipv4.dscp_class());
+
+        //0x2a is AF11 (10) with ECT(0)
+        let (_, (_, ipv6)) = parse_ip_header("6,0x2a,,64,tcp,6,").unwrap();
+        let IpSpecific::Ipv6(ipv6) = ipv6 else {
+            panic!("expected an IPv6 header");
+        };
+        assert_eq!(DscpClass::Af11, ipv6.dscp_class());
+
+        assert_eq!(DscpClass::Default, DscpClass::from_dscp(0));
+        assert_eq!(DscpClass::Cs7, DscpClass::from_dscp(56));
+        assert_eq!(DscpClass::Af43, DscpClass::from_dscp(38));
+        assert_eq!(DscpClass::Other(1), DscpClass::from_dscp(1));
+    }
+
+    #[test]
+    fn parse_ipv4_header_hex_id() {
+        let (_, (_, decimal)) = parse_ip_header("4,0x0,,127,61633,0,DF,6,tcp,").unwrap();
+        let (_, (_, hex)) = parse_ip_header("4,0x0,,127,0xF0C1,0,DF,6,tcp,").unwrap();
+        assert_eq!(decimal, hex);
+        assert_eq!("4,0x0,,127,61633,0,DF", hex.to_log_fields());
+    }
+
+    #[test]
+    fn hop_limit_test() {
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,127,61633,0,DF,6,tcp,").unwrap();
+        assert_eq!(127, ipv4.hop_limit());
+
+        let (_, (_, ipv6)) = parse_ip_header("6,0x00,,64,tcp,6,").unwrap();
+        assert_eq!(64, ipv6.hop_limit());
+    }
+
+    #[test]
+    fn ip_specific_display() {
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,127,61633,0,DF,6,tcp,").unwrap();
+        assert_eq!("IPv4 ttl=127 id=61633 DF", ipv4.to_string());
+
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,64,1,185,none,17,udp,").unwrap();
+        assert_eq!("IPv4 ttl=64 id=1 off=185", ipv4.to_string());
+
+        let (_, (_, ipv6)) = parse_ip_header("6,0x00,,64,tcp,6,").unwrap();
+        assert_eq!("IPv6 hlim=64 class=0x00", ipv6.to_string());
+    }
+
+    #[test]
+    fn to_log_fields_test() {
+        let (_, (_, ipv4)) = parse_ip_header("4,0x0,,127,61633,0,DF,6,tcp,").unwrap();
+        assert_eq!("4,0x0,,127,61633,0,DF", ipv4.to_log_fields());
+
+        let (_, (_, ipv6)) = parse_ip_header("6,0x00,abc12,64,tcp,6,").unwrap();
+        assert_eq!("6,0x00,abc12,64", ipv6.to_log_fields());
+    }
+
+    #[test]
+    fn parse_ipv6_header_ext_headers() {
+        let (next, (protocol, ipv6)) =
+            parse_ip_header("6,0x00,,64,hopopt,0,ipv6-route,43,tcp,6,60,2001:db8::1,2001:db8::2,")
+                .unwrap();
+        assert_eq!("60,2001:db8::1,2001:db8::2,", next);
+        assert_eq!(
+            Protocol {
+                name: ProtoName::Tcp,
+                num: 6
+            },
+            protocol
+        );
+        let IpSpecific::Ipv6(ipv6) = ipv6 else {
+            panic!("expected an IPv6 header");
+        };
+        assert_eq!(vec![0, 43], ipv6.ext_headers);
+
+        //A lone extension header is kept as the protocol
+        let (next, (protocol, ipv6)) =
+            parse_ip_header("6,0x00,,64,hopopt,0,60,2001:db8::1,2001:db8::2,").unwrap();
+        assert_eq!("60,2001:db8::1,2001:db8::2,", next);
+        assert_eq!(0, protocol.num);
+        let IpSpecific::Ipv6(ipv6) = ipv6 else {
+            panic!("expected an IPv6 header");
+        };
+        assert!(ipv6.ext_headers.is_empty());
+    }
+
+    #[test]
+    fn parse_ipv6_header_blank_protoname() {
+        let (next, (protocol, _)) =
+            parse_ip_header("6,0x00,,64,,17,60,2001:db8::1,2001:db8::2,").unwrap();
+        assert_eq!("60,2001:db8::1,2001:db8::2,", next);
+        assert_eq!(
+            Protocol {
+                name: ProtoName::Udp,
+                num: 17
+            },
+            protocol
+        );
+    }
+
+    #[test]
+    fn flow_label_value_test() {
+        let ipv6 = |flow_label: &str| IpV6 {
+            traffic_class: 0,
+            flow_label: flow_label.into(),
+            hoplimit: 64,
+            ext_headers: Vec::new(),
+        };
+
+        assert_eq!(Some(0xabc12), ipv6("abc12").flow_label_value());
+        assert_eq!(Some(0xabc12), ipv6("0xabc12").flow_label_value());
+        assert_eq!(None, ipv6("").flow_label_value());
+
+        //Over the 20 bits range: masked, the raw value stays available
+        assert_eq!(Some(0x1abc12), ipv6("1abc12").flow_label_raw());
+        assert_eq!(Some(0xabc12), ipv6("1abc12").flow_label_value());
+    }
+
+    #[test]
+    fn parse_ip_header_version_fail() {
+        let unknown_version = "5,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        assert!(parse_ip_header(unknown_version).is_err());
+
+        let not_a_number = "4a,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        assert!(parse_ip_header(not_a_number).is_err());
+    }
+
+    #[test]
+    fn parse_inner_ip_header_test() {
+        let ipip = Protocol {
+            name: ProtoName::Other("ipencap".into()),
+            num: 4,
+        };
+        let inner = "4,0x0,,64,1,0,none,17,udp,49678,161,86";
+
+        let expected = IpSpecific::IpV4(IpV4 {
+            version: 4,
+            tos: 0,
+            ecn: None,
+            ttl: 64,
+            id: 1,
+            offset: 0,
+            flags: "none".into(),
+        });
+
+        assert_eq!(
+            Ok((
+                "49678,161,86",
+                Some((
+                    Protocol {
+                        name: ProtoName::Udp,
+                        num: 17
+                    },
+                    expected
+                ))
+            )),
+            parse_inner_ip_header(inner, &ipip)
+        );
+
+        //Tunnels without an inner header are left untouched
+        assert_eq!(Ok(("raw", None)), parse_inner_ip_header("raw", &ipip));
+
+        //Non tunnel protocols never parse an inner header
+        let udp = Protocol {
+            name: ProtoName::Udp,
+            num: 17,
+        };
+        assert_eq!(Ok((inner, None)), parse_inner_ip_header(inner, &udp));
+    }
 }
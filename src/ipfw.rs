@@ -0,0 +1,248 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use nom::{
+    bytes::complete::{tag, take_till, take_till1},
+    character::complete::{char, space1, u16 as parse_u16, u32 as parse_u32, u8 as parse_u8},
+    combinator::{eof, fail, map_res, opt, rest},
+    sequence::{preceded, separated_pair, terminated},
+    IResult, Parser,
+};
+
+use crate::log::{LogParseError, LogParseErrorKind};
+use crate::packet_filter::{Action, Dir};
+use crate::protocol::ProtoName;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///A log entry of FreeBSD's ipfw, as in
+/// `ipfw: 100 Deny TCP 10.0.0.1:1234 10.0.0.2:80 in via em0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IpfwLog {
+    pub rule_number: u32,
+    /// The ipfw action as logged (e.g. `Accept`, `Deny`, `Unreach 3`).
+    pub action: String,
+    pub protocol: ProtoName,
+    /// The ICMP type and code, logged as `ICMP:8.0`.
+    pub icmp_type_code: Option<(u8, u8)>,
+    pub src: IpAddr,
+    pub src_port: Option<u16>,
+    pub dst: IpAddr,
+    pub dst_port: Option<u16>,
+    pub dir: Dir,
+    pub interface: String,
+    /// Anything logged after the interface, such as fragment information.
+    pub details: Option<String>,
+}
+
+impl IpfwLog {
+    /// Maps the ipfw action to the equivalent pf action, `None` for actions
+    /// that don't decide the fate of the packet (`Count`, `Divert`, ...).
+    pub fn pf_action(&self) -> Option<Action> {
+        match self.action.split(' ').next() {
+            Some("Accept") => Some(Action::Pass),
+            Some("Deny") => Some(Action::Block),
+            Some("Reset" | "Unreach") => Some(Action::Reject),
+            _ => None,
+        }
+    }
+}
+
+fn parse_action(input: &str) -> IResult<&str, String> {
+    let (next, action) = take_till1(|c| c == ' ')(input)?;
+    //Unreach carries the ICMP code it answers with
+    match action {
+        "Unreach" => preceded(char(' '), take_till1(|c| c == ' '))
+            .map(|code| format!("{} {}", action, code))
+            .parse(next),
+        action => Ok((next, action.into())),
+    }
+}
+
+///The protocol with the ICMP type and code logged along with it.
+type ProtocolToken = (ProtoName, Option<(u8, u8)>);
+
+fn parse_protocol(input: &str) -> IResult<&str, ProtocolToken> {
+    let (next, token) = take_till1(|c| c == ' ')(input)?;
+
+    let protocol = match token.split_once(':') {
+        //Protocols without a name are logged as P:<num>
+        Some(("P", num)) => {
+            map_res(rest, u8::from_str)
+                .map(ProtoName::from_num)
+                .parse(num)?
+                .1
+        }
+        Some((name, type_code)) => {
+            let (_, type_code) = separated_pair(parse_u8, char('.'), parse_u8)(type_code)?;
+            return Ok((next, (proto_name(name), Some(type_code))));
+        }
+        None => proto_name(token),
+    };
+
+    Ok((next, (protocol, None)))
+}
+
+fn proto_name(name: &str) -> ProtoName {
+    //Infallible, unknown names become Other
+    ProtoName::from_str(&name.to_lowercase()).unwrap()
+}
+
+///Parses `addr`, `addr:port` or `[addr]:port` (IPv6 with a port).
+fn parse_endpoint(input: &str) -> IResult<&str, (IpAddr, Option<u16>)> {
+    let (next, token) = take_till1(|c| c == ' ')(input)?;
+
+    let (addr, port) = if let Some(bracketed) = token.strip_prefix('[') {
+        let (addr, port) = bracketed.split_once(']').unwrap_or((bracketed, ""));
+        (addr, port.strip_prefix(':'))
+    } else {
+        match token.split_once(':') {
+            //A single colon separates an IPv4 address and its port
+            Some((addr, port)) if !port.contains(':') => (addr, Some(port)),
+            _ => (token, None),
+        }
+    };
+
+    let (_, addr) = map_res(rest, IpAddr::from_str)(addr)?;
+    let port = match port {
+        Some(port) => Some(terminated(parse_u16, eof)(port)?.1),
+        None => None,
+    };
+
+    Ok((next, (addr, port)))
+}
+
+fn parse_dir(input: &str) -> IResult<&str, Dir> {
+    let (next, dir) = take_till1(|c| c == ' ')(input)?;
+    match Dir::from_str(dir) {
+        Ok(dir) => Ok((next, dir)),
+        Err(_) => fail(input),
+    }
+}
+
+fn parse_ipfw(input: &str) -> IResult<&str, IpfwLog> {
+    let (next, _) = opt(tag("ipfw: "))(input)?;
+    let (next, rule_number) = terminated(parse_u32, space1)(next)?;
+    let (next, action) = terminated(parse_action, space1)(next)?;
+    let (next, (protocol, icmp_type_code)) = terminated(parse_protocol, space1)(next)?;
+    let (next, (src, src_port)) = terminated(parse_endpoint, space1)(next)?;
+    let (next, (dst, dst_port)) = terminated(parse_endpoint, space1)(next)?;
+    let (next, dir) = terminated(parse_dir, tag(" via "))(next)?;
+    let (next, interface) = take_till1(|c: char| c.is_ascii_whitespace())(next)?;
+    let (next, details) = opt(preceded(space1, take_till(|c| c == '\n')))(next)?;
+
+    let details = details
+        .map(|details: &str| details.trim_end())
+        .filter(|details| !details.is_empty())
+        .map(|details| details.into());
+
+    let ipfw_log = IpfwLog {
+        rule_number,
+        action,
+        protocol,
+        icmp_type_code,
+        src,
+        src_port,
+        dst,
+        dst_port,
+        dir,
+        interface: interface.into(),
+        details,
+    };
+
+    Ok((next, ipfw_log))
+}
+
+/// Parses a single ipfw log entry, with or without its `ipfw: ` prefix.
+///
+/// # Example
+/// ```rust
+/// use senpa::ipfw::parse_ipfw_log;
+/// use senpa::{Action, Dir, ProtoName};
+///
+/// let log = parse_ipfw_log("ipfw: 100 Deny TCP 10.0.0.1:1234 10.0.0.2:80 in via em0").unwrap();
+/// assert_eq!(100, log.rule_number);
+/// assert_eq!(Some(Action::Block), log.pf_action());
+/// assert_eq!(ProtoName::Tcp, log.protocol);
+/// assert_eq!(Some(80), log.dst_port);
+/// assert_eq!(Dir::In, log.dir);
+/// ```
+pub fn parse_ipfw_log(input: &str) -> Result<IpfwLog, LogParseError> {
+    match parse_ipfw(input) {
+        Ok((next, ipfw_log)) if next.trim_end().is_empty() => Ok(ipfw_log),
+        _ => Err(LogParseError {
+            raw_log: input.into(),
+            reason: "Failed to parse ipfw log".into(),
+            kind: LogParseErrorKind::Malformed,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_ipfw_tcp() {
+        assert_eq!(
+            Ok(IpfwLog {
+                rule_number: 65535,
+                action: "Accept".into(),
+                protocol: ProtoName::Tcp,
+                icmp_type_code: None,
+                src: "192.168.1.2".parse().unwrap(),
+                src_port: Some(52461),
+                dst: "192.168.1.1".parse().unwrap(),
+                dst_port: Some(22),
+                dir: Dir::Out,
+                interface: "em0".into(),
+                details: None,
+            }),
+            parse_ipfw_log("65535 Accept TCP 192.168.1.2:52461 192.168.1.1:22 out via em0\n")
+        );
+    }
+
+    #[test]
+    fn parse_ipfw_icmp_and_unnamed() {
+        let log =
+            parse_ipfw_log("ipfw: 200 Unreach 3 ICMP:8.0 10.0.0.1 10.0.0.2 in via igb1").unwrap();
+        assert_eq!("Unreach 3", log.action);
+        assert_eq!(Some(Action::Reject), log.pf_action());
+        assert_eq!(ProtoName::Icmp, log.protocol);
+        assert_eq!(Some((8, 0)), log.icmp_type_code);
+        assert_eq!(None, log.src_port);
+
+        let log = parse_ipfw_log("ipfw: 300 Count P:47 10.0.0.1 10.0.0.2 in via em0").unwrap();
+        assert_eq!(ProtoName::Other("gre".into()), log.protocol);
+        assert_eq!(None, log.pf_action());
+    }
+
+    #[test]
+    fn parse_ipfw_ipv6_and_details() {
+        let log = parse_ipfw_log(
+            "ipfw: 100 Deny UDP [2001:db8::1]:5353 [2001:db8::2]:53 in via em0 Fragment = 185",
+        )
+        .unwrap();
+        assert_eq!("2001:db8::1".parse::<IpAddr>().unwrap(), log.src);
+        assert_eq!(Some(5353), log.src_port);
+        assert_eq!(Some(53), log.dst_port);
+        assert_eq!(Some("Fragment = 185".into()), log.details);
+
+        let log = parse_ipfw_log("ipfw: 100 Deny ICMPv6:128.0 fe80::1 ff02::1 in via em0").unwrap();
+        assert_eq!("fe80::1".parse::<IpAddr>().unwrap(), log.src);
+        assert_eq!(Some((128, 0)), log.icmp_type_code);
+    }
+
+    #[test]
+    fn parse_ipfw_fail() {
+        assert!(
+            parse_ipfw_log("ipfw: 100 Deny TCP 10.0.0.1:99999 10.0.0.2:80 in via em0").is_err()
+        );
+        assert!(
+            parse_ipfw_log("ipfw: 100 Deny TCP 10.0.0.1:1 10.0.0.2:80 sideways via em0").is_err()
+        );
+        assert!(parse_ipfw_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::flow::FiveTuple;
+use crate::log::FwLog;
+
+///Filter and map adapters for iterators of parsed logs, to write pipelines declaratively.
+///
+/// Implemented for every `Iterator<Item = FwLog>` and exported by the prelude.
+///
+/// # Example
+/// ```rust
+/// use senpa::batch::parse_logs;
+/// use senpa::prelude::*;
+///
+/// let input = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,block,in,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86\n\
+/// 96,,,fae559338f65e11c53669fc3642c93c2,igb0,match,block,in,\
+/// 4,0x0,,127,58941,0,none,17,udp,\
+/// 106,192.168.10.16,192.168.20.11,49679,161,86\n\
+/// 97,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,in,\
+/// 4,0x0,,127,58942,0,none,17,udp,\
+/// 106,192.168.10.17,192.168.20.11,49680,161,86\n";
+///
+/// let sources: Vec<_> = parse_logs(input)
+///     .into_iter()
+///     .flatten()
+///     .blocked()
+///     .on_interface("vlan0.20")
+///     .to_port(161)
+///     .five_tuples()
+///     .map(|tuple| tuple.src.to_string())
+///     .collect();
+/// assert_eq!(vec!["192.168.10.15"], sources);
+/// ```
+pub trait LogIterExt: Iterator<Item = FwLog> + Sized {
+    /// Keeps the logs of denied packets, see [`crate::Action::is_deny`].
+    fn blocked(self) -> impl Iterator<Item = FwLog> {
+        self.filter(|log| log.packet_filter.action.is_deny())
+    }
+
+    /// Keeps the logs of the given interface.
+    fn on_interface(self, name: &str) -> impl Iterator<Item = FwLog> {
+        self.filter(move |log| log.packet_filter.interface == name)
+    }
+
+    /// Keeps the logs of packets sent to the given destination port.
+    fn to_port(self, port: u16) -> impl Iterator<Item = FwLog> {
+        self.filter(move |log| log.ports().is_some_and(|(_, dstport)| dstport == port))
+    }
+
+    /// Maps each log to its [`FiveTuple`].
+    fn five_tuples(self) -> impl Iterator<Item = FiveTuple> {
+        self.map(|log| log.five_tuple())
+    }
+}
+
+impl<I: Iterator<Item = FwLog>> LogIterExt for I {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_log;
+
+    #[test]
+    fn log_iter_ext_test() {
+        let udp = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,58940,0,none,17,udp,\
+            106,192.168.10.15,192.168.20.11,49678,161,86",
+        )
+        .unwrap();
+        let icmp = parse_log(
+            "97,,,02f4bab031b57d1e30553ce08e0ec131,em0,match,block,in,\
+            4,0x0,,64,12345,0,none,1,icmp,\
+            84,10.0.0.5,192.168.1.1,request,1234,1",
+        )
+        .unwrap();
+        let logs = vec![udp.clone(), icmp.clone()];
+
+        assert_eq!(
+            vec![icmp.clone()],
+            logs.clone().into_iter().blocked().collect::<Vec<_>>()
+        );
+        assert_eq!(1, logs.clone().into_iter().on_interface("em0").count());
+        assert_eq!(
+            vec![udp.clone()],
+            logs.clone().into_iter().to_port(161).collect::<Vec<_>>()
+        );
+        assert_eq!(0, logs.clone().into_iter().to_port(49678).count());
+        assert_eq!(
+            vec![udp.five_tuple(), icmp.five_tuple()],
+            logs.into_iter().five_tuples().collect::<Vec<_>>()
+        );
+    }
+}
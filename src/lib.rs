@@ -1,9 +1,21 @@
 #![doc = include_str!("../README.md")]
 
+pub mod analysis;
+pub mod batch;
+pub mod event;
+mod fields;
+pub mod flow;
 pub mod ip;
+pub mod ipfw;
+pub mod iter;
 pub mod log;
+pub mod options;
 pub mod packet_filter;
+pub mod parsers;
 pub mod protocol;
+pub mod redact;
+#[cfg(feature = "serde-hex")]
+mod serde_hex;
 #[doc(hidden)]
 mod utils;
 
@@ -12,12 +24,17 @@ mod utils;
 pub use self::log::parse_log;
 #[doc(inline)]
 pub use self::log::FwLog;
+pub use self::options::Dialect;
+pub use self::options::ParseOptions;
 pub use self::packet_filter::Action;
 pub use self::packet_filter::Dir;
+pub use self::packet_filter::Disposition;
 pub use self::protocol::ProtoInfo;
 pub use self::protocol::ProtoName;
+pub use self::redact::Redacted;
 
 pub mod prelude {
+    pub use crate::iter::LogIterExt;
     pub use crate::packet_filter::Action::*;
     pub use crate::packet_filter::Dir::*;
     pub use crate::parse_log;
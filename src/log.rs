@@ -1,19 +1,57 @@
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt::Display;
+use std::net::SocketAddr;
+use std::time::Duration;
 
-use crate::ip::{parse_ip_data, parse_ip_header, IpData, IpSpecific};
+use crate::ip::{
+    parse_inner_ip_header, parse_ip_data, parse_ip_header, IpData, IpSpecific, IPV6_FRAGMENT,
+    MAX_FLOW_LABEL,
+};
+use crate::options::{Dialect, ParseOptions};
 use crate::packet_filter::parse_packet_filter;
-use crate::packet_filter::PacketFilter;
-use crate::protocol::parse_proto_info;
+use crate::packet_filter::{Action, PacketFilter};
+use crate::packet_filter::{BlockCause, KnownInterface, TypedInterface};
+use crate::protocol::{has_port_out_of_range, parse_proto_info, ProtoName};
 use crate::protocol::{ProtoInfo, Protocol};
+use crate::utils::{
+    split_addr_ports, split_leading_timestamp, strip_link_layer_prefix, strip_truncation_marker,
+    trim_fields,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+///Whether a line failed because it was cut short or because of an unexpected token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogParseErrorKind {
+    /// The line ended before a field could be read, or carried a truncation marker.
+    ///
+    /// Callers reading a file being rotated may buffer such lines and retry them.
+    Truncated,
+    /// A field didn't have the expected form.
+    Malformed,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct LogParseError {
     pub raw_log: String,
     pub reason: String,
+    pub kind: LogParseErrorKind,
+}
+
+///Classifies a failed parser: running into the end of the line means it was cut short.
+fn error_kind(error: &nom::Err<nom::error::Error<&str>>, truncated: bool) -> LogParseErrorKind {
+    if truncated {
+        return LogParseErrorKind::Truncated;
+    }
+    match error {
+        nom::Err::Incomplete(_) => LogParseErrorKind::Truncated,
+        nom::Err::Error(e) | nom::Err::Failure(e) if e.input.trim_end().is_empty() => {
+            LogParseErrorKind::Truncated
+        }
+        _ => LogParseErrorKind::Malformed,
+    }
 }
 
 impl Display for LogParseError {
@@ -42,6 +80,273 @@ pub struct FwLog {
     pub protocol: Protocol,
     /// Protocol-specific information, including flags and control data.
     pub proto_info: ProtoInfo,
+    /// The encapsulated IP header for IP-in-IP (protocol 4) and 6in4 (protocol 41) tunnels, when logged.
+    pub inner: Option<Box<IpSpecific>>,
+    /// The encapsulated protocol of a tunnel, which [`FwLog::proto_info`] was parsed for, when logged.
+    pub inner_protocol: Option<Protocol>,
+    /// `true` when the line ended with a truncation marker appended by the syslog transport.
+    ///
+    /// The marker is stripped before parsing; the last logged field may be incomplete.
+    pub truncated: bool,
+    /// The time since the Unix epoch of a leading timestamp field, see [`ParseOptions::leading_timestamp`].
+    pub timestamp: Option<Duration>,
+}
+
+impl FwLog {
+    /// Returns the on-wire length of the packet, as logged in the IP data.
+    ///
+    /// This is the figure to use for bandwidth accounting.
+    pub fn byte_count(&self) -> u32 {
+        u32::from(self.ip_data.length)
+    }
+
+    /// Returns the `(rule number, action, interface)` key rule hits are counted by,
+    /// see [`crate::analysis::rule_hits`].
+    pub fn rule_hit_key(&self) -> (u32, Action, String) {
+        (
+            self.packet_filter.rule_info.number,
+            self.packet_filter.action.clone(),
+            self.packet_filter.interface.clone(),
+        )
+    }
+
+    /// Returns `true` if the packet was blocked by one of the catch-all rules in `default_rule_numbers`.
+    ///
+    /// Rejects are not counted, as default deny rules block silently.
+    pub fn is_default_deny(&self, default_rule_numbers: &[u32]) -> bool {
+        self.packet_filter.action == Action::Block
+            && default_rule_numbers.contains(&self.packet_filter.rule_info.number)
+    }
+
+    /// Returns `(srcport, dstport)` for port-bearing protocols (TCP, UDP), `None` otherwise.
+    pub fn ports(&self) -> Option<(u16, u16)> {
+        self.proto_info
+            .ports()
+            .map(|ports| (ports.srcport, ports.dstport))
+    }
+
+    ///Returns `(service, client)` endpoints, `None` for protocols without ports.
+    fn endpoints(&self) -> Option<(SocketAddr, SocketAddr)> {
+        let (srcport, dstport) = self.ports()?;
+        let src = SocketAddr::new(self.ip_data.src, srcport);
+        let dst = SocketAddr::new(self.ip_data.dst, dstport);
+
+        //The lower port is the well-known or registered one, ties go to the destination
+        if srcport < dstport {
+            Some((src, dst))
+        } else {
+            Some((dst, src))
+        }
+    }
+
+    /// Returns the server side of the packet: the endpoint with the lower port,
+    /// which is the well-known or registered one. `None` for protocols without ports.
+    ///
+    /// # Example
+    /// ```rust
+    /// use senpa::parse_log;
+    ///
+    /// let reply = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,in,\
+    /// 4,0x0,,64,1234,0,none,17,udp,\
+    /// 120,192.168.20.11,192.168.10.15,161,49678,100").unwrap();
+    ///
+    /// assert_eq!("192.168.20.11:161", reply.service_endpoint().unwrap().to_string());
+    /// assert_eq!("192.168.10.15:49678", reply.client_endpoint().unwrap().to_string());
+    /// ```
+    pub fn service_endpoint(&self) -> Option<SocketAddr> {
+        self.endpoints().map(|(service, _)| service)
+    }
+
+    /// Returns the client side of the packet, the endpoint opposite to [`FwLog::service_endpoint`].
+    pub fn client_endpoint(&self) -> Option<SocketAddr> {
+        self.endpoints().map(|(_, client)| client)
+    }
+
+    /// Returns the protocol [`FwLog::proto_info`] was parsed for:
+    /// the encapsulated one for tunnels, [`FwLog::protocol`] otherwise.
+    pub fn payload_protocol(&self) -> &Protocol {
+        self.inner_protocol.as_ref().unwrap_or(&self.protocol)
+    }
+
+    /// Returns the TTL (IPv4) or hop limit (IPv6) of the packet.
+    pub fn ttl(&self) -> u8 {
+        self.ip_specific.hop_limit()
+    }
+
+    /// Returns `true` if the packet is a fragment.
+    ///
+    /// The transport fields of non-initial fragments aren't headers and must not be read as ports.
+    /// IPv6 fragments are detected by a logged fragment header (next header 44), either among
+    /// the extension headers or as the protocol itself.
+    pub fn is_fragment(&self) -> bool {
+        match &self.ip_specific {
+            IpSpecific::IpV4(ipv4) => ipv4.is_fragment(),
+            IpSpecific::Ipv6(ipv6) => {
+                ipv6.ext_headers.contains(&IPV6_FRAGMENT) || self.protocol.num == IPV6_FRAGMENT
+            }
+        }
+    }
+
+    /// Classifies why the packet was blocked or rejected, `None` when it was passed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use senpa::packet_filter::BlockCause;
+    /// use senpa::parse_log;
+    ///
+    /// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,state-limit,block,in,\
+    /// 4,0x0,,127,58940,0,none,17,udp,\
+    /// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+    ///
+    /// assert_eq!(Some(BlockCause::ResourceExhaustion), log.block_cause());
+    /// ```
+    pub fn block_cause(&self) -> Option<BlockCause> {
+        self.packet_filter.block_cause()
+    }
+
+    /// Returns a labeled multi-line dump of the log for CLI inspection, one field per line.
+    ///
+    /// Empty optional fields are omitted; protocol details are indented under `Payload:`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use senpa::parse_log;
+    ///
+    /// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+    /// 4,0x0,,127,58940,0,none,17,udp,\
+    /// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+    ///
+    /// let pretty = log.to_pretty();
+    /// assert!(pretty.starts_with("Rule: 96\n"));
+    /// assert!(pretty.contains("\nInterface: vlan0.20\n"));
+    /// assert!(pretty.contains("\n5-tuple: udp 192.168.10.15:49678 -> 192.168.20.11:161\n"));
+    /// ```
+    pub fn to_pretty(&self) -> String {
+        let rule_info = &self.packet_filter.rule_info;
+        let mut lines = vec![format!("Rule: {}", rule_info.rule_id())];
+        if !rule_info.label.is_empty() {
+            lines.push(format!("Label: {}", rule_info.label));
+        }
+        if let Some(anchorname) = &rule_info.anchorname {
+            lines.push(format!("Anchor: {}", anchorname));
+        }
+        if let Some(ridentifier) = rule_info.ridentifier {
+            lines.push(format!("Rule identifier: {}", ridentifier));
+        }
+        if let Some(tracker) = rule_info.tracker {
+            lines.push(format!("Tracker: {}", tracker));
+        }
+        lines.push(format!("Interface: {}", self.packet_filter.interface));
+        lines.push(format!("Reason: {}", self.packet_filter.reason));
+        lines.push(format!("Action: {}", self.packet_filter.action));
+        if let Some(reject_method) = self.packet_filter.reject_method {
+            lines.push(format!("Reject method: {}", reject_method));
+        }
+        lines.push(format!("Direction: {}", self.packet_filter.dir));
+        lines.push(format!("IP: {}", self.ip_specific));
+        if let Some(inner) = &self.inner {
+            lines.push(format!("Inner IP: {}", inner));
+        }
+
+        let (src, dst) = match self.ports() {
+            Some((srcport, dstport)) => (
+                SocketAddr::new(self.ip_data.src, srcport).to_string(),
+                SocketAddr::new(self.ip_data.dst, dstport).to_string(),
+            ),
+            None => (self.ip_data.src.to_string(), self.ip_data.dst.to_string()),
+        };
+        lines.push(format!(
+            "5-tuple: {} {} -> {}",
+            self.payload_protocol().name,
+            src,
+            dst
+        ));
+        lines.push(format!("Length: {}", self.ip_data.length));
+
+        let payload: Vec<(&str, String)> = match &self.proto_info {
+            ProtoInfo::TcpInfo(tcp_info) => vec![
+                ("Flags", tcp_info.flags.clone()),
+                ("Timing", tcp_info.timing().to_string()),
+                ("Data length", tcp_info.data_len.to_string()),
+                ("Options", tcp_info.options.to_string()),
+            ],
+            ProtoInfo::UnknownInfo(raw) => vec![("Raw", raw.clone())],
+            proto_info => vec![("Fields", proto_info.to_log_fields())],
+        };
+        let payload: Vec<String> = payload
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(label, value)| format!("  {}: {}", label, value))
+            .collect();
+        if !payload.is_empty() {
+            lines.push("Payload:".into());
+            lines.extend(payload);
+        }
+
+        if self.truncated {
+            lines.push("Truncated: yes".into());
+        }
+        if let Some(timestamp) = self.timestamp {
+            lines.push(format!("Timestamp: {:.6}", timestamp.as_secs_f64()));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Returns a tcpdump-like one line summary of the log.
+    ///
+    /// Ports are only shown for port-bearing protocols, and TCP flags are shown in brackets.
+    ///
+    /// # Example
+    /// ```rust
+    /// use senpa::parse_log;
+    ///
+    /// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,block,in,\
+    /// 4,0x0,,127,61633,0,DF,6,tcp,\
+    /// 52,192.168.10.15,192.168.20.14,\
+    /// 52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK").unwrap();
+    ///
+    /// assert_eq!(
+    ///     "block in vlan0.20 TCP 192.168.10.15:52461 > 192.168.20.14:9100 [S] len 52",
+    ///     log.to_summary()
+    /// );
+    /// ```
+    pub fn to_summary(&self) -> String {
+        let (src, dst) = match self.ports() {
+            Some((srcport, dstport)) => (
+                SocketAddr::new(self.ip_data.src, srcport).to_string(),
+                SocketAddr::new(self.ip_data.dst, dstport).to_string(),
+            ),
+            None => (self.ip_data.src.to_string(), self.ip_data.dst.to_string()),
+        };
+
+        let flags = match &self.proto_info {
+            ProtoInfo::TcpInfo(tcp_info) => format!(" [{}]", tcp_info.flags),
+            _ => String::new(),
+        };
+
+        format!(
+            "{} {} {} {} {} > {}{} len {}",
+            self.packet_filter.action,
+            self.packet_filter.dir,
+            self.packet_filter.interface,
+            self.payload_protocol().name.to_string().to_uppercase(),
+            src,
+            dst,
+            flags,
+            self.ip_data.length
+        )
+    }
+}
+
+///A parsed log paired with the exact line it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParsedLine {
+    /// The raw line, as given to the parser.
+    pub raw: String,
+    /// The log parsed from `raw`.
+    pub log: FwLog,
 }
 
 /// Parses a single log entry from the given input string.
@@ -86,50 +391,383 @@ pub struct FwLog {
 /// }
 /// ```
 pub fn parse_log(input: &str) -> Result<FwLog, LogParseError> {
-    let (next, packet_filter) = parse_packet_filter(input).map_err(|_| LogParseError {
+    parse_log_with(input, &ParseOptions::default())
+}
+
+/// Parses a single log entry like [`parse_log`], keeping the raw line alongside the result.
+///
+/// The line is moved into the returned [`ParsedLine`], so it is never copied on success.
+///
+/// # Example
+/// ```rust
+/// use senpa::log::parse_log_keep_raw;
+///
+/// let input = String::from("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86");
+///
+/// let parsed = parse_log_keep_raw(input.clone()).unwrap();
+/// assert_eq!(input, parsed.raw);
+/// assert_eq!(96, parsed.log.packet_filter.rule_info.number);
+/// ```
+pub fn parse_log_keep_raw(input: String) -> Result<ParsedLine, LogParseError> {
+    let log = parse_log(&input)?;
+    Ok(ParsedLine { raw: input, log })
+}
+
+/// Parses a single log entry from raw bytes, as read from a file or socket.
+///
+/// Returns a `LogParseError` if the bytes aren't valid UTF-8, otherwise behaves like [`parse_log`].
+///
+/// # Example
+/// ```rust
+/// use senpa::log::parse_log_bytes;
+///
+/// let input = b"96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86";
+///
+/// assert!(parse_log_bytes(input).is_ok());
+/// assert!(parse_log_bytes(b"96,\xff").is_err());
+/// ```
+pub fn parse_log_bytes(input: &[u8]) -> Result<FwLog, LogParseError> {
+    let input = std::str::from_utf8(input).map_err(|_| LogParseError {
+        raw_log: String::from_utf8_lossy(input).into(),
+        reason: "Invalid UTF-8".into(),
+        kind: LogParseErrorKind::Malformed,
+    })?;
+
+    parse_log(input)
+}
+
+/// Parses a single log entry like [`parse_log`], using the given [`ParseOptions`].
+///
+/// # Example
+/// ```rust
+/// use senpa::log::parse_log_with;
+/// use senpa::options::{Dialect, ParseOptions};
+///
+/// let input = "5,,,1000000103,igb1,match,block,in,\
+/// 4,0x0,,64,0,0,DF,17,udp,\
+/// 328,192.168.1.1,192.168.1.255,138,138,308";
+///
+/// let options = ParseOptions {
+///     dialect: Dialect::PfSense,
+///     ..Default::default()
+/// };
+/// let log = parse_log_with(input, &options).unwrap();
+/// assert_eq!(Some(1000000103), log.packet_filter.rule_info.ridentifier);
+/// ```
+pub fn parse_log_with(input: &str, options: &ParseOptions) -> Result<FwLog, LogParseError> {
+    //Errors always report the raw input, even when the parsed line is rewritten
+    let (mut start, mut timestamp) = (input, None);
+    if options.leading_timestamp {
+        (timestamp, start) = split_leading_timestamp(start);
+    }
+    if options.strip_link_prefix {
+        start = strip_link_layer_prefix(start);
+    }
+    let mut line = Cow::Borrowed(start);
+    if options.trim_fields {
+        line = Cow::Owned(trim_fields(&line));
+    }
+    if options.combined_addr_port {
+        if let Some(split) = split_addr_ports(&line) {
+            line = Cow::Owned(split);
+        }
+    }
+    let (line, truncated) = strip_truncation_marker(line.as_ref(), options.ellipsis_truncation);
+
+    let (next, packet_filter) = parse_packet_filter(line, options).map_err(|e| LogParseError {
         raw_log: input.into(),
         reason: "Failed to parse packet filter".into(),
+        kind: error_kind(&e, truncated),
     })?;
 
-    let (next, (protocol, ip_header)) = parse_ip_header(next).map_err(|_| LogParseError {
+    if options.numeric_labels && packet_filter.rule_info.label_as_u64().is_none() {
+        return Err(LogParseError {
+            raw_log: input.into(),
+            reason: "Label is not numeric".into(),
+            kind: LogParseErrorKind::Malformed,
+        });
+    }
+
+    let (next, (protocol, ip_header)) = parse_ip_header(next).map_err(|e| LogParseError {
         raw_log: input.into(),
         reason: "Failed to parse IP header".into(),
+        kind: error_kind(&e, truncated),
     })?;
 
-    let (next, ip_data) = parse_ip_data(next, &ip_header).map_err(|_| LogParseError {
+    if let (true, IpSpecific::Ipv6(ipv6)) = (options.strict_flow_label, &ip_header) {
+        if ipv6
+            .flow_label_raw()
+            .is_some_and(|label| label > MAX_FLOW_LABEL)
+        {
+            return Err(LogParseError {
+                raw_log: input.into(),
+                reason: "Flow label exceeds 20 bits".into(),
+                kind: LogParseErrorKind::Malformed,
+            });
+        }
+    }
+
+    let (next, ip_data) = parse_ip_data(next, &ip_header).map_err(|e| LogParseError {
         raw_log: input.into(),
         reason: "Failed to parse IP data".into(),
+        kind: error_kind(&e, truncated),
     })?;
 
-    let (_, proto_info) = parse_proto_info(next, &protocol.name).map_err(|_| LogParseError {
+    let (next, inner) = parse_inner_ip_header(next, &protocol).map_err(|e| LogParseError {
         raw_log: input.into(),
-        reason: "Failed to parse protocol-specific information".into(),
+        reason: "Failed to parse inner IP header".into(),
+        kind: error_kind(&e, truncated),
     })?;
 
+    //The payload of a tunnel is described by the encapsulated protocol
+    let payload_proto = match &inner {
+        Some((inner_protocol, _)) => &inner_protocol.name,
+        None => &protocol.name,
+    };
+
+    let (next, proto_info) =
+        parse_proto_info(next, payload_proto, options).map_err(|e| LogParseError {
+            raw_log: input.into(),
+            reason: match payload_proto {
+                ProtoName::Tcp | ProtoName::Udp | ProtoName::Dccp
+                    if has_port_out_of_range(next) =>
+                {
+                    "Failed to parse protocol-specific information: port out of range".into()
+                }
+                _ => "Failed to parse protocol-specific information".into(),
+            },
+            kind: error_kind(&e, truncated),
+        })?;
+
+    if options.exact && !next.trim_end().is_empty() {
+        return Err(LogParseError {
+            raw_log: input.into(),
+            reason: "Unexpected trailing data".into(),
+            kind: LogParseErrorKind::Malformed,
+        });
+    }
+
     let firewall_log = FwLog {
         packet_filter,
         ip_specific: ip_header,
         ip_data,
         protocol,
         proto_info,
+        inner_protocol: inner
+            .as_ref()
+            .map(|(inner_protocol, _)| inner_protocol.clone()),
+        inner: inner.map(|(_, ip_specific)| Box::new(ip_specific)),
+        truncated,
+        timestamp,
     };
 
     Ok(firewall_log)
 }
 
+/// Parses a single log entry trying each [`Dialect`] in turn, returning the one that matched.
+///
+/// Dialects are tried in the order of [`Dialect::ALL`]. On failure, the error of the last
+/// dialect tried is returned.
+///
+/// # Example
+/// ```rust
+/// use senpa::log::parse_log_detect;
+/// use senpa::Dialect;
+///
+/// let (dialect, log) = parse_log_detect("5,,,1000000103,igb1,match,block,in,\
+/// 4,0x0,,64,0,0,DF,17,udp,\
+/// 328,192.168.1.1,192.168.1.255,138,138,308").unwrap();
+/// assert_eq!(Dialect::PfSense, dialect);
+/// assert_eq!(Some(1000000103), log.packet_filter.rule_info.ridentifier);
+/// ```
+pub fn parse_log_detect(input: &str) -> Result<(Dialect, FwLog), LogParseError> {
+    let mut last_error = None;
+    for dialect in Dialect::ALL {
+        let options = ParseOptions {
+            dialect,
+            ..Default::default()
+        };
+        match parse_log_with(input, &options) {
+            Ok(log) => return Ok((dialect, log)),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    //Infallible, Dialect::ALL is not empty
+    Err(last_error.unwrap())
+}
+
+/// Parses a single log entry like [`parse_log`], failing if the line has extra trailing columns.
+///
+/// # Example
+/// ```rust
+/// use senpa::log::parse_log_exact;
+///
+/// let input = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,61633,0,DF,6,tcp,\
+/// 52,192.168.10.15,192.168.20.14,\
+/// 52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK";
+///
+/// assert!(parse_log_exact(input).is_ok());
+/// assert!(parse_log_exact(&format!("{},extra", input)).is_err());
+/// ```
+pub fn parse_log_exact(input: &str) -> Result<FwLog, LogParseError> {
+    let options = ParseOptions {
+        exact: true,
+        ..Default::default()
+    };
+    parse_log_with(input, &options)
+}
+
+///Returns `true` if a record starts at the beginning of `input`: its packet filter and IP header parse.
+fn is_record_start(input: &str, options: &ParseOptions) -> bool {
+    parse_packet_filter(input, options)
+        .and_then(|(next, _)| parse_ip_header(next))
+        .is_ok()
+}
+
+/// Parses the first of several log entries concatenated without a separator,
+/// returning it with the input left after it.
+///
+/// The entry ends where the next one starts: at a rule number followed by a valid
+/// packet filter and IP header, such that the text before it is a complete entry.
+/// If no entry follows, the whole input is parsed and the returned rest is empty.
+///
+/// A split at the start of a run of digits, leaving the last field of the entry whole, is preferred.
+/// When the entry ends with a number (e.g. UDP data length), its digits run into the next rule
+/// number; the split is then the one whose IPv4 UDP length agrees with the IP length, and an
+/// error is returned if that doesn't single out one split.
+///
+/// # Example
+/// ```rust
+/// use senpa::log::parse_log_with_rest;
+/// use senpa::ParseOptions;
+///
+/// let tcp = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,61633,0,DF,6,tcp,\
+/// 52,192.168.10.15,192.168.20.14,\
+/// 52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK";
+/// let udp = "97,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86";
+///
+/// let input = format!("{}{}", tcp, udp);
+/// let (log, rest) = parse_log_with_rest(&input, &ParseOptions::default()).unwrap();
+/// assert_eq!(96, log.packet_filter.rule_info.number);
+/// assert_eq!(udp, rest);
+/// ```
+pub fn parse_log_with_rest<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<(FwLog, &'a str), LogParseError> {
+    let split = |i: usize| match is_record_start(&input[i..], options) {
+        true => parse_log_with(&input[..i], options).ok(),
+        false => None,
+    };
+
+    let first = input
+        .char_indices()
+        .skip(1)
+        .filter(|(_, c)| c.is_ascii_digit())
+        .find_map(|(i, _)| split(i).map(|log| (i, log)));
+    let Some((first, log)) = first else {
+        return Ok((parse_log_with(input, options)?, ""));
+    };
+
+    //The split can only move within the run of digits it falls in
+    let run_start = input[..first]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    if first == run_start {
+        return Ok((log, &input[first..]));
+    }
+    let run_end = input[first..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(input.len(), |i| first + i);
+
+    let mut candidates: Vec<(usize, FwLog)> = std::iter::once((first, log))
+        .chain((first + 1..run_end).filter_map(|i| split(i).map(|log| (i, log))))
+        .collect();
+    if candidates.len() > 1 {
+        candidates.retain(|(_, log)| udp_length_matches(log));
+    }
+
+    match candidates.pop() {
+        Some((end, log)) if candidates.is_empty() => Ok((log, &input[end..])),
+        _ => Err(LogParseError {
+            raw_log: input.into(),
+            reason: "Ambiguous boundary between concatenated entries".into(),
+            kind: LogParseErrorKind::Malformed,
+        }),
+    }
+}
+
+///Returns `true` for an IPv4 UDP log whose IP length minus the UDP length is a valid IPv4 header length.
+fn udp_length_matches(log: &FwLog) -> bool {
+    match (&log.ip_specific, &log.proto_info) {
+        (IpSpecific::IpV4(_), ProtoInfo::UdpInfo(udp_info)) => u32::from(log.ip_data.length)
+            .checked_sub(udp_info.data_len)
+            .is_some_and(|header| (20..=60).contains(&header)),
+        _ => false,
+    }
+}
+
+/// Parses a log entry like [`parse_log`], also mapping its interface to the user registered set `K`.
+///
+/// Interfaces that `K` doesn't recognize are returned as [`TypedInterface::Other`],
+/// so parsing never fails because of an unexpected interface name.
+///
+/// # Example
+/// ```rust
+/// use senpa::log::parse_log_typed;
+/// use senpa::packet_filter::{KnownInterface, TypedInterface};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Site {
+///     Lan,
+/// }
+///
+/// impl KnownInterface for Site {
+///     fn from_name(name: &str) -> Option<Self> {
+///         (name == "vlan0.20").then_some(Site::Lan)
+///     }
+/// }
+///
+/// let input = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86";
+///
+/// let (interface, _log) = parse_log_typed::<Site>(input).unwrap();
+/// assert_eq!(TypedInterface::Known(Site::Lan), interface);
+/// ```
+pub fn parse_log_typed<K: KnownInterface>(
+    input: &str,
+) -> Result<(TypedInterface<K>, FwLog), LogParseError> {
+    let log = parse_log(input)?;
+    Ok((log.packet_filter.typed_interface(), log))
+}
+
 #[cfg(test)]
 mod test {
 
     use super::*;
     use crate::ip::IpV4;
+    use crate::ip::IpV6;
     use crate::packet_filter::Action::*;
     use crate::packet_filter::Dir::*;
     use crate::packet_filter::Reason::*;
     use crate::packet_filter::RuleInfo;
+    use crate::protocol::IcmpInfo;
     use crate::protocol::Ports;
     use crate::protocol::ProtoName::*;
     use crate::protocol::TcpInfo;
+    use crate::protocol::TcpOption;
+    use crate::protocol::TcpOptions;
     use crate::protocol::UdpInfo;
+    use crate::protocol::VrrpInfo;
     use std::net::IpAddr;
     use std::net::Ipv4Addr;
     use std::str::FromStr;
@@ -149,10 +787,13 @@ mod test {
                         subrulenr: None,
                         anchorname: None,
                         label: "fae559338f65e11c53669fc3642c93c2".into(),
+                        ridentifier: None,
+                        tracker: None,
                     },
                     interface: "vlan0.20".into(),
                     reason: Match,
                     action: Pass,
+                    reject_method: None,
                     dir: Out,
                 },
                 ip_specific: IpSpecific::IpV4(IpV4 {
@@ -181,8 +822,19 @@ mod test {
                     ack_number: None,
                     window: 64240,
                     urg: None,
-                    options: "mss;nop;wscale;nop;nop;sackOK".into(),
+                    options: TcpOptions(vec![
+                        TcpOption::Mss,
+                        TcpOption::Nop,
+                        TcpOption::Wscale,
+                        TcpOption::Nop,
+                        TcpOption::Nop,
+                        TcpOption::SackOk,
+                    ]),
                 },),
+                inner: None,
+                inner_protocol: None,
+                truncated: false,
+                timestamp: None,
             },
             flog
         );
@@ -202,10 +854,13 @@ mod test {
                         subrulenr: None,
                         anchorname: None,
                         label: "fae559338f65e11c53669fc3642c93c2".into(),
+                        ridentifier: None,
+                        tracker: None,
                     },
                     interface: "vlan0.20".into(),
                     reason: Match,
                     action: Pass,
+                    reject_method: None,
                     dir: Out,
                 },
                 ip_specific: IpSpecific::IpV4(IpV4 {
@@ -230,11 +885,690 @@ mod test {
                     },
                     data_len: 86,
                 },),
+                inner: None,
+                inner_protocol: None,
+                truncated: false,
+                timestamp: None,
             }),
             flog
         );
     }
 
+    #[test]
+    fn it_works_ipip() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,4,ipencap,\
+        126,203.0.113.1,198.51.100.1,\
+        4,0x0,,64,1,0,none,17,udp,\
+        49678,161,86";
+        let flog = parse_log(log).unwrap();
+
+        assert_eq!(
+            Protocol {
+                num: 4,
+                name: Other("ipencap".into())
+            },
+            flog.protocol
+        );
+        assert_eq!(
+            Some(Box::new(IpSpecific::IpV4(IpV4 {
+                version: 4,
+                tos: 0,
+                ecn: None,
+                ttl: 64,
+                id: 1,
+                offset: 0,
+                flags: "none".into(),
+            }))),
+            flog.inner
+        );
+        assert_eq!(
+            ProtoInfo::UdpInfo(UdpInfo {
+                ports: Ports {
+                    srcport: 49678,
+                    dstport: 161,
+                },
+                data_len: 86,
+            }),
+            flog.proto_info
+        );
+        assert_eq!(
+            Some(Protocol {
+                num: 17,
+                name: ProtoName::Udp
+            }),
+            flog.inner_protocol
+        );
+        assert_eq!(17, flog.payload_protocol().num);
+        assert_eq!(17, flog.five_tuple().proto);
+    }
+
+    #[test]
+    fn it_works_numeric_vrrp() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0xc0,,255,0,0,none,112,112,\
+        40,10.0.0.1,224.0.0.18,2,5,100";
+        let flog = parse_log(log).unwrap();
+        assert_eq!(
+            Protocol {
+                num: 112,
+                name: ProtoName::Vrrp
+            },
+            flog.protocol
+        );
+        assert_eq!(
+            ProtoInfo::VrrpInfo(VrrpInfo {
+                version: Some(2),
+                vrid: Some(5),
+                priority: Some(100),
+            }),
+            flog.proto_info
+        );
+        assert_eq!(
+            flog,
+            parse_log(&log.replace("112,112", "112,vrrp")).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_works_pfsense_dialect() {
+        let log = "5,,,1000000103,igb1,match,block,in,\
+        4,0x0,,64,0,0,DF,17,udp,\
+        328,192.168.1.1,192.168.1.255,138,138,308";
+        let options = ParseOptions {
+            dialect: Dialect::PfSense,
+            ..Default::default()
+        };
+
+        let flog = parse_log_with(log, &options).unwrap();
+        assert_eq!(
+            PacketFilter {
+                rule_info: RuleInfo {
+                    number: 5,
+                    subrulenr: None,
+                    anchorname: None,
+                    label: String::new(),
+                    ridentifier: Some(1000000103),
+                    tracker: None,
+                },
+                interface: "igb1".into(),
+                reason: Match,
+                action: Block,
+                reject_method: None,
+                dir: In,
+            },
+            flog.packet_filter
+        );
+
+        //The default dialect reads the identifier as a label
+        let flog = parse_log(log).unwrap();
+        assert_eq!("1000000103", flog.packet_filter.rule_info.label);
+        assert_eq!(None, flog.packet_filter.rule_info.ridentifier);
+    }
+
+    #[test]
+    fn parse_log_leading_timestamp() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let options = ParseOptions {
+            leading_timestamp: true,
+            ..Default::default()
+        };
+
+        let flog = parse_log_with(&format!("1718187322.123456,{}", log), &options).unwrap();
+        assert_eq!(Some(Duration::new(1718187322, 123456000)), flog.timestamp);
+        assert_eq!(parse_log(log).unwrap().packet_filter, flog.packet_filter);
+
+        let flog = parse_log_with(log, &options).unwrap();
+        assert_eq!(None, flog.timestamp);
+
+        //The default options don't read it
+        assert!(parse_log(&format!("1718187322.123456,{}", log)).is_err());
+    }
+
+    #[test]
+    fn it_works_tracker_dialect() {
+        let log = "1700000000123,,,96,vlan0.20,match,block,in,\
+        4,0x0,,64,0,0,DF,17,udp,\
+        328,192.168.1.1,192.168.1.255,138,138,308";
+        let options = ParseOptions {
+            dialect: Dialect::Tracker,
+            ..Default::default()
+        };
+
+        let flog = parse_log_with(log, &options).unwrap();
+        assert_eq!(96, flog.packet_filter.rule_info.number);
+        assert_eq!(Some(1700000000123), flog.packet_filter.rule_info.tracker);
+
+        assert!(parse_log(log).is_err());
+        let (dialect, _) = parse_log_detect(log).unwrap();
+        assert_eq!(Dialect::Tracker, dialect);
+    }
+
+    #[test]
+    fn error_kind_truncated() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+
+        //Cut in the IP header, then in the ports
+        for cut in [log.find("589").unwrap() + 3, log.len() - 5] {
+            let err = parse_log(&log[..cut]).unwrap_err();
+            assert_eq!(LogParseErrorKind::Truncated, err.kind, "{}", err.reason);
+        }
+
+        //A failure on a line with a truncation marker
+        let err = parse_log(&format!("{} [truncated]", &log[..log.len() - 5])).unwrap_err();
+        assert_eq!(LogParseErrorKind::Truncated, err.kind);
+
+        let err = parse_log(&log.replace("58940", "58x40")).unwrap_err();
+        assert_eq!(LogParseErrorKind::Malformed, err.kind);
+    }
+
+    #[test]
+    fn to_pretty_test() {
+        let log = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,61633,0,DF,6,tcp,\
+            52,192.168.10.15,192.168.20.14,\
+            52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK",
+        )
+        .unwrap();
+
+        assert_eq!(
+            "Rule: 96\n\
+            Label: fae559338f65e11c53669fc3642c93c2\n\
+            Interface: vlan0.20\n\
+            Reason: match\n\
+            Action: pass\n\
+            Direction: out\n\
+            IP: IPv4 ttl=127 id=61633 DF\n\
+            5-tuple: tcp 192.168.10.15:52461 -> 192.168.20.14:9100\n\
+            Length: 52\n\
+            Payload:\n  \
+            Flags: S\n  \
+            Timing: seq 3442468761, win 64240\n  \
+            Data length: 0\n  \
+            Options: mss;nop;wscale;nop;nop;sackOK",
+            log.to_pretty()
+        );
+    }
+
+    #[test]
+    fn parse_log_detect_test() {
+        let pfsense = "5,,,1000000103,igb1,match,block,in,\
+        4,0x0,,64,0,0,DF,17,udp,\
+        328,192.168.1.1,192.168.1.255,138,138,308";
+        let (dialect, _) = parse_log_detect(pfsense).unwrap();
+        assert_eq!(Dialect::PfSense, dialect);
+
+        let opnsense = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let (dialect, log) = parse_log_detect(opnsense).unwrap();
+        assert_eq!(Dialect::OpnSense, dialect);
+        assert_eq!(parse_log(opnsense).unwrap(), log);
+
+        assert_eq!(
+            "Failed to parse packet filter",
+            parse_log_detect("not a log").unwrap_err().reason
+        );
+    }
+
+    #[test]
+    fn ports_test() {
+        let tcp = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,61633,0,DF,6,tcp,\
+        52,192.168.10.15,192.168.20.14,\
+        52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK",
+        )
+        .unwrap();
+        assert_eq!(Some((52461, 9100)), tcp.ports());
+
+        let mut udp = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86",
+        )
+        .unwrap();
+        assert_eq!(Some((49678, 161)), udp.ports());
+
+        udp.protocol = Protocol::from_name(Icmp);
+        udp.proto_info = ProtoInfo::IcmpInfo(IcmpInfo {
+            icmp_type: "request".into(),
+            type_num: Some(8),
+            code: None,
+            id: Some(29474),
+            seq: Some(1),
+            embedded: None,
+            mtu: None,
+            details: None,
+        });
+        assert_eq!(None, udp.ports());
+    }
+
+    #[test]
+    fn to_summary_test() {
+        let mut udp = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86",
+        )
+        .unwrap();
+        assert_eq!(
+            "pass out vlan0.20 UDP 192.168.10.15:49678 > 192.168.20.11:161 len 106",
+            udp.to_summary()
+        );
+
+        udp.ip_data.src = "2001:db8::1".parse().unwrap();
+        udp.ip_data.dst = "2001:db8::2".parse().unwrap();
+        assert_eq!(
+            "pass out vlan0.20 UDP [2001:db8::1]:49678 > [2001:db8::2]:161 len 106",
+            udp.to_summary()
+        );
+
+        udp.protocol = Protocol::from_name(Icmp);
+        udp.proto_info = ProtoInfo::IcmpInfo(IcmpInfo {
+            icmp_type: "request".into(),
+            type_num: Some(8),
+            code: None,
+            id: Some(29474),
+            seq: Some(1),
+            embedded: None,
+            mtu: None,
+            details: None,
+        });
+        assert_eq!(
+            "pass out vlan0.20 ICMP 2001:db8::1 > 2001:db8::2 len 106",
+            udp.to_summary()
+        );
+    }
+
+    #[test]
+    fn parse_log_bytes_test() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        assert_eq!(parse_log(log), parse_log_bytes(log.as_bytes()));
+
+        assert_eq!(
+            Err(LogParseError {
+                raw_log: "96,\u{FFFD}".into(),
+                reason: "Invalid UTF-8".into(),
+                kind: LogParseErrorKind::Malformed,
+            }),
+            parse_log_bytes(b"96,\xff")
+        );
+    }
+
+    #[test]
+    fn trim_fields_option() {
+        let padded = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20, match,pass ,out,\
+        4,0x0,,127,58940,0,none,17, udp ,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let options = ParseOptions {
+            trim_fields: true,
+            ..Default::default()
+        };
+
+        let flog = parse_log_with(padded, &options).unwrap();
+        assert_eq!(Pass, flog.packet_filter.action);
+        assert_eq!(Protocol { num: 17, name: Udp }, flog.protocol);
+
+        //Strict by default
+        assert_eq!(
+            Err(LogParseError {
+                raw_log: padded.into(),
+                reason: "Failed to parse packet filter".into(),
+                kind: LogParseErrorKind::Malformed,
+            }),
+            parse_log(padded)
+        );
+    }
+
+    #[test]
+    fn strip_link_prefix_option() {
+        let log = "vlan 20,96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let options = ParseOptions {
+            strip_link_prefix: true,
+            ..Default::default()
+        };
+
+        let flog = parse_log_with(log, &options).unwrap();
+        assert_eq!(96, flog.packet_filter.rule_info.number);
+        assert_eq!("vlan0.20", flog.packet_filter.interface);
+
+        assert!(parse_log(log).is_err());
+    }
+
+    #[test]
+    fn combined_addr_port_option() {
+        let combined = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,61633,0,DF,6,tcp,\
+        52,192.168.10.15:52461,192.168.20.14:9100,\
+        0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK";
+        let options = ParseOptions {
+            combined_addr_port: true,
+            ..Default::default()
+        };
+
+        let flog = parse_log_with(combined, &options).unwrap();
+        assert_eq!(Some((52461, 9100)), flog.ports());
+        assert_eq!("192.168.20.14".parse::<IpAddr>().unwrap(), flog.ip_data.dst);
+
+        assert!(parse_log(combined).is_err());
+    }
+
+    #[test]
+    fn truncation_marker() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        assert!(!parse_log(log).unwrap().truncated);
+
+        //A trailing ellipsis is data by default
+        assert!(parse_log(&format!("{}...", log)).is_err());
+        let options = ParseOptions {
+            ellipsis_truncation: true,
+            ..Default::default()
+        };
+        let flog = parse_log_with(&format!("{}...", log), &options).unwrap();
+        assert!(flog.truncated);
+        assert_eq!(Some((49678, 161)), flog.ports());
+
+        let gre = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,47,gre,\
+        106,192.168.10.15,192.168.20.11,payload...";
+        let flog = parse_log(gre).unwrap();
+        assert!(!flog.truncated);
+        assert_eq!(ProtoInfo::UnknownInfo("payload...".into()), flog.proto_info);
+
+        let tcp = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,61633,0,DF,6,tcp,\
+        52,192.168.10.15,192.168.20.14,\
+        52461,9100,0,S,3442468761,,64240,,mss;nop;wsc [truncated]";
+        let flog = parse_log(tcp).unwrap();
+        assert!(flog.truncated);
+        assert!(
+            matches!(flog.proto_info, ProtoInfo::TcpInfo(tcp_info) if tcp_info.options.to_string() == "mss;nop;wsc")
+        );
+    }
+
+    #[test]
+    fn it_works_icmp() {
+        let log = "97,,,02f4bab031b57d1e30553ce08e0ec131,em0,match,pass,out,\
+        4,0x0,,64,12345,0,none,1,icmp,\
+        84,192.168.1.1,8.8.8.8,request,29474,1";
+        let flog = parse_log(log).unwrap();
+
+        assert_eq!(Protocol { num: 1, name: Icmp }, flog.protocol);
+        assert_eq!(
+            ProtoInfo::IcmpInfo(IcmpInfo {
+                icmp_type: "request".into(),
+                type_num: Some(8),
+                code: None,
+                id: Some(29474),
+                seq: Some(1),
+                embedded: None,
+                mtu: None,
+                details: None,
+            }),
+            flog.proto_info
+        );
+    }
+
+    #[test]
+    fn parse_log_exact_test() {
+        let tcp = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,61633,0,DF,6,tcp,\
+        52,192.168.10.15,192.168.20.14,\
+        52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK";
+        assert_eq!(parse_log(tcp), parse_log_exact(tcp));
+
+        let extra = format!("{},1,2", tcp);
+        assert!(parse_log(&extra).is_ok());
+        assert_eq!(
+            Err(LogParseError {
+                raw_log: extra.clone(),
+                reason: "Unexpected trailing data".into(),
+                kind: LogParseErrorKind::Malformed,
+            }),
+            parse_log_exact(&extra)
+        );
+
+        let udp = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        assert!(parse_log_exact(udp).is_ok());
+        assert!(parse_log_exact(&format!("{},1", udp)).is_err());
+    }
+
+    #[test]
+    fn parse_log_keep_raw_test() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        assert_eq!(
+            Ok(ParsedLine {
+                raw: log.into(),
+                log: parse_log(log).unwrap()
+            }),
+            parse_log_keep_raw(log.into())
+        );
+
+        assert_eq!(
+            Err(LogParseError {
+                raw_log: "garbage".into(),
+                reason: "Failed to parse packet filter".into(),
+                kind: LogParseErrorKind::Malformed,
+            }),
+            parse_log_keep_raw("garbage".into())
+        );
+    }
+
+    #[test]
+    fn it_works_dash_placeholders() {
+        let log = "96,,-,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,-,127,61633,0,DF,6,tcp,\
+        52,192.168.10.15,192.168.20.14,\
+        52461,9100,0,S,3442468761,-,64240,-,mss;nop;wscale;nop;nop;sackOK";
+        let flog = parse_log(log).unwrap();
+
+        assert_eq!(None, flog.packet_filter.rule_info.anchorname);
+        let IpSpecific::IpV4(ipv4) = &flog.ip_specific else {
+            panic!("expected an IPv4 header");
+        };
+        assert_eq!(None, ipv4.ecn);
+        let ProtoInfo::TcpInfo(tcp_info) = &flog.proto_info else {
+            panic!("expected TcpInfo");
+        };
+        assert_eq!(None, tcp_info.ack_number);
+        assert_eq!(None, tcp_info.urg);
+        assert_eq!(64240, tcp_info.window);
+    }
+
+    #[test]
+    fn it_works_ipv6_blank_flow_label() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        6,0x00,,64,udp,17,\
+        60,2001:db8::1,2001:db8::2,49678,161,52";
+        let flog = parse_log(log).unwrap();
+
+        assert_eq!(
+            IpSpecific::Ipv6(IpV6 {
+                traffic_class: 0,
+                flow_label: String::new(),
+                hoplimit: 64,
+                ext_headers: Vec::new(),
+            }),
+            flog.ip_specific
+        );
+        assert_eq!(Some((49678, 161)), flog.ports());
+    }
+
+    #[test]
+    fn it_works_empty_label() {
+        let log = "96,,,,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let flog = parse_log(log).unwrap();
+        assert_eq!("", flog.packet_filter.rule_info.label);
+        assert_eq!("vlan0.20", flog.packet_filter.interface);
+    }
+
+    #[test]
+    fn it_works_numeric_protoname() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,igb0,match,pass,in,\
+        4,0x0,,64,4321,0,none,50,50,\
+        120,203.0.113.1,198.51.100.1,";
+        let flog = parse_log(log).unwrap();
+        assert_eq!(
+            Protocol {
+                num: 50,
+                name: Other("esp".into())
+            },
+            flog.protocol
+        );
+    }
+
+    #[test]
+    fn it_works_anchor_path() {
+        let log = "5,1,natearly/1,fae559338f65e11c53669fc3642c93c2,igb0,match,pass,in,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let flog = parse_log(log).unwrap();
+
+        let rule_info = &flog.packet_filter.rule_info;
+        assert_eq!(Some(1), rule_info.subrulenr);
+        assert_eq!(Some("natearly/1".into()), rule_info.anchorname);
+        assert_eq!("fae559338f65e11c53669fc3642c93c2", rule_info.label);
+        assert_eq!("igb0", flog.packet_filter.interface);
+        assert_eq!(Some((49678, 161)), flog.ports());
+    }
+
+    #[test]
+    fn numeric_labels_option() {
+        let options = ParseOptions {
+            numeric_labels: true,
+            ..Default::default()
+        };
+        let log = "96,,,1000000103,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let flog = parse_log_with(log, &options).unwrap();
+        assert_eq!(
+            Some(1000000103),
+            flog.packet_filter.rule_info.label_as_u64()
+        );
+
+        let log = log.replace("1000000103", "fae559338f65e11c53669fc3642c93c2");
+        assert_eq!(
+            Err(LogParseError {
+                raw_log: log.clone(),
+                reason: "Label is not numeric".into(),
+                kind: LogParseErrorKind::Malformed,
+            }),
+            parse_log_with(&log, &options)
+        );
+        assert!(parse_log(&log).is_ok());
+    }
+
+    #[test]
+    fn port_out_of_range_fail() {
+        let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,70000,161,86";
+        assert_eq!(
+            Err(LogParseError {
+                raw_log: log.into(),
+                reason: "Failed to parse protocol-specific information: port out of range".into(),
+                kind: LogParseErrorKind::Malformed,
+            }),
+            parse_log(log)
+        );
+    }
+
+    #[test]
+    fn strict_flow_label_option() {
+        let log = |flow_label: &str| {
+            format!(
+                "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+                6,0x00,{},64,udp,17,\
+                60,2001:db8::1,2001:db8::2,49678,161,52",
+                flow_label
+            )
+        };
+        let strict = ParseOptions {
+            strict_flow_label: true,
+            ..Default::default()
+        };
+
+        assert!(parse_log_with(&log("fffff"), &strict).is_ok());
+        assert!(parse_log_with(&log(""), &strict).is_ok());
+        assert_eq!(
+            Err(LogParseError {
+                raw_log: log("100000"),
+                reason: "Flow label exceeds 20 bits".into(),
+                kind: LogParseErrorKind::Malformed,
+            }),
+            parse_log_with(&log("100000"), &strict)
+        );
+
+        let lenient = parse_log(&log("100001")).unwrap();
+        let IpSpecific::Ipv6(ipv6) = lenient.ip_specific else {
+            panic!("expected an IPv6 header");
+        };
+        assert_eq!(Some(1), ipv6.flow_label_value());
+    }
+
+    #[test]
+    fn is_fragment_test() {
+        let udp = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            6,0x00,,64,udp,17,\
+            60,2001:db8::1,2001:db8::2,49678,161,52",
+        )
+        .unwrap();
+        assert!(!udp.is_fragment());
+
+        let fragment = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            6,0x00,,64,ipv6-frag,44,udp,17,\
+            60,2001:db8::1,2001:db8::2,49678,161,52",
+        )
+        .unwrap();
+        let IpSpecific::Ipv6(ipv6) = &fragment.ip_specific else {
+            panic!("expected an IPv6 header");
+        };
+        assert_eq!(vec![44], ipv6.ext_headers);
+        assert!(fragment.is_fragment());
+
+        let ipv4 = parse_log(
+            "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+            4,0x0,,127,58940,185,none,17,udp,\
+            106,192.168.10.15,192.168.20.11,49678,161,86",
+        )
+        .unwrap();
+        assert!(ipv4.is_fragment());
+    }
+
+    #[test]
+    fn is_default_deny_test() {
+        let log = "4,,,02f4bab031b57d1e30553ce08e0ec131,igb0,match,block,in,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+        let flog = parse_log(log).unwrap();
+        assert!(flog.is_default_deny(&[4, 5]));
+        assert!(!flog.is_default_deny(&[96]));
+
+        let passed = parse_log(&log.replace("block", "pass")).unwrap();
+        assert!(!passed.is_default_deny(&[4, 5]));
+    }
+
     #[test]
     fn packet_filter_fail() {
         let log = "ab,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
@@ -243,7 +1577,8 @@ mod test {
         assert_eq!(
             Err(LogParseError {
                 raw_log: log.into(),
-                reason: "Failed to parse packet filter".into()
+                reason: "Failed to parse packet filter".into(),
+                kind: LogParseErrorKind::Malformed,
             }),
             parse_log(log)
         );
@@ -257,7 +1592,8 @@ mod test {
         assert_eq!(
             Err(LogParseError {
                 raw_log: log.into(),
-                reason: "Failed to parse IP header".into()
+                reason: "Failed to parse IP header".into(),
+                kind: LogParseErrorKind::Malformed,
             }),
             parse_log(log)
         );
@@ -271,7 +1607,8 @@ mod test {
         assert_eq!(
             Err(LogParseError {
                 raw_log: log.into(),
-                reason: "Failed to parse IP data".into()
+                reason: "Failed to parse IP data".into(),
+                kind: LogParseErrorKind::Malformed,
             }),
             parse_log(log)
         );
@@ -285,7 +1622,8 @@ mod test {
         assert_eq!(
             Err(LogParseError {
                 raw_log: log.into(),
-                reason: "Failed to parse protocol-specific information".into()
+                reason: "Failed to parse protocol-specific information".into(),
+                kind: LogParseErrorKind::Malformed,
             }),
             parse_log(log)
         );
@@ -0,0 +1,92 @@
+use crate::protocol::ProtoName;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///The layout variant of the filterlog CSV line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Dialect {
+    /// OPNsense layout: the fourth column is the rule label.
+    #[default]
+    OpnSense,
+    /// pfSense layout: the fourth column is the numeric rule identifier (`ridentifier`).
+    PfSense,
+    /// Tracker layout: the first column is a 64-bit tracker ID and the fourth column
+    /// is the rule number.
+    Tracker,
+}
+
+impl Dialect {
+    /// Every dialect, in the order [`crate::log::parse_log_detect`] tries them:
+    /// stricter layouts first, so a line is only read as OPNsense when no other layout fits.
+    pub const ALL: [Dialect; 3] = [Dialect::PfSense, Dialect::Tracker, Dialect::OpnSense];
+}
+
+///Options to tune how a log line is parsed, used by [`crate::log::parse_log_with`].
+///
+/// The default options parse the same layout as [`crate::parse_log`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// The layout of the line.
+    pub dialect: Dialect,
+    /// The protocols whose payload is parsed into a structured `ProtoInfo`, all of them when `None`.
+    ///
+    /// The payload of any other protocol is kept raw in `ProtoInfo::UnknownInfo`.
+    pub structured_protocols: Option<Vec<ProtoName>>,
+    /// Trims leading and trailing ASCII whitespace from every field before matching it.
+    ///
+    /// Off by default, so padded tokens such as ` tcp` are rejected.
+    pub trim_fields: bool,
+    /// Requires the whole line to be consumed, failing on extra trailing columns.
+    ///
+    /// Off by default, so extra columns after the TCP options are folded into them.
+    pub exact: bool,
+    /// Requires the rule label to be a numeric rule ID, see [`crate::packet_filter::RuleInfo::label_as_u64`].
+    ///
+    /// Off by default, so any label is accepted.
+    pub numeric_labels: bool,
+    /// Accepts IPv4 source and destination logged with their ports, as `192.168.10.15:52461`,
+    /// in place of the separate address and port columns.
+    ///
+    /// Off by default, so colons are only read as part of IPv6 addresses.
+    pub combined_addr_port: bool,
+    /// Rejects IPv6 flow labels that don't fit in 20 bits.
+    ///
+    /// Off by default, so out of range labels are masked by [`crate::ip::IpV6::flow_label_value`].
+    pub strict_flow_label: bool,
+    /// Strips the link-layer tokens some sources prepend to the line, before the rule number:
+    /// a `vlan N` tag and/or a hexadecimal ethertype such as `0x8100`.
+    ///
+    /// Off by default, so the line must start with the rule number.
+    pub strip_link_prefix: bool,
+    /// Reads the TCP urgent pointer and ack number columns in swapped positions,
+    /// `seq,urg,window,ack`, as some firmware builds log them.
+    ///
+    /// Off by default, so the columns are read in the usual `seq,ack,window,urg` order.
+    pub swapped_urg_ack: bool,
+    /// Reads a leading epoch timestamp field with a fractional part, as `1718187322.123456,`,
+    /// into [`crate::FwLog::timestamp`]. Lines without it are parsed as usual.
+    ///
+    /// Off by default, so the line must start with the rule number.
+    pub leading_timestamp: bool,
+    /// Accepts lines missing the reason column, as logged by older pf builds, reading them as
+    /// [`crate::packet_filter::Reason::Match`].
+    ///
+    /// Off by default, so the reason column is required.
+    pub optional_reason: bool,
+    /// Reads a trailing `...` as a truncation marker appended by the syslog transport,
+    /// like `[truncated]`, see [`crate::FwLog::truncated`].
+    ///
+    /// Off by default, so a trailing `...` is kept as part of the last field.
+    pub ellipsis_truncation: bool,
+}
+
+impl ParseOptions {
+    pub(crate) fn is_structured(&self, proto: &ProtoName) -> bool {
+        match &self.structured_protocols {
+            Some(protocols) => protocols.contains(proto),
+            None => true,
+        }
+    }
+}
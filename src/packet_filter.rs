@@ -1,14 +1,15 @@
-use std::{error::Error, str::FromStr};
+use std::{error::Error, fmt::Display, str::FromStr};
 
-use crate::utils::{csv, parse_utf8_string};
+use crate::options::{Dialect, ParseOptions};
+use crate::utils::{csv, opt_field, parse_utf8_string};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{alphanumeric1, char, u32 as parse_u32},
+    bytes::complete::{tag, take_till, take_till1},
+    character::complete::{char, u32 as parse_u32, u64 as parse_u64},
     combinator::{fail, opt, peek},
     sequence::terminated,
     IResult, Parser,
@@ -16,7 +17,6 @@ use nom::{
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-
 pub enum Dir {
     In,
     Out,
@@ -44,7 +44,35 @@ impl FromStr for Dir {
     }
 }
 
-fn parse_dir(input: &str) -> IResult<&str, Dir> {
+impl Display for Dir {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dir::In => write!(f, "in"),
+            Dir::Out => write!(f, "out"),
+        }
+    }
+}
+
+impl Dir {
+    /// Returns a stable numeric code for columnar storage: `In` is 0, `Out` is 1.
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Dir::In => 0,
+            Dir::Out => 1,
+        }
+    }
+
+    /// Returns the direction with the code given by [`Dir::as_u8`].
+    pub fn from_u8(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Dir::In),
+            1 => Some(Dir::Out),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn parse_dir(input: &str) -> IResult<&str, Dir> {
     let (next, dir) = terminated(alt((tag("in"), tag("out"))), peek(char(',')))(input)?;
     if let Ok(dir) = Dir::from_str(dir) {
         Ok((next, dir))
@@ -53,10 +81,26 @@ fn parse_dir(input: &str) -> IResult<&str, Dir> {
     }
 }
 
+///Why pf logged the packet, as in pf's reason codes.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Reason {
     Match,
+    BadOffset,
+    Fragment,
+    Short,
+    Normalize,
+    Memory,
+    BadTimestamp,
+    Congestion,
+    IpOption,
+    ProtoCksum,
+    StateMismatch,
+    StateInsert,
+    StateLimit,
+    SrcLimit,
+    Synproxy,
+    MapFailed,
 }
 
 impl FromStr for Reason {
@@ -64,13 +108,88 @@ impl FromStr for Reason {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "match" => Ok(Reason::Match),
+            "bad-offset" => Ok(Reason::BadOffset),
+            "fragment" => Ok(Reason::Fragment),
+            "short" => Ok(Reason::Short),
+            "normalize" => Ok(Reason::Normalize),
+            "memory" => Ok(Reason::Memory),
+            "bad-timestamp" => Ok(Reason::BadTimestamp),
+            "congestion" => Ok(Reason::Congestion),
+            "ip-option" => Ok(Reason::IpOption),
+            "proto-cksum" => Ok(Reason::ProtoCksum),
+            "state-mismatch" => Ok(Reason::StateMismatch),
+            "state-insert" => Ok(Reason::StateInsert),
+            "state-limit" => Ok(Reason::StateLimit),
+            "src-limit" => Ok(Reason::SrcLimit),
+            "synproxy" => Ok(Reason::Synproxy),
+            "map-failed" => Ok(Reason::MapFailed),
             _ => Err(()),
         }
     }
 }
 
-fn parse_reason(input: &str) -> IResult<&str, Reason> {
-    let (next, reason) = terminated(tag("match"), peek(char(',')))(input)?;
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Match => write!(f, "match"),
+            Reason::BadOffset => write!(f, "bad-offset"),
+            Reason::Fragment => write!(f, "fragment"),
+            Reason::Short => write!(f, "short"),
+            Reason::Normalize => write!(f, "normalize"),
+            Reason::Memory => write!(f, "memory"),
+            Reason::BadTimestamp => write!(f, "bad-timestamp"),
+            Reason::Congestion => write!(f, "congestion"),
+            Reason::IpOption => write!(f, "ip-option"),
+            Reason::ProtoCksum => write!(f, "proto-cksum"),
+            Reason::StateMismatch => write!(f, "state-mismatch"),
+            Reason::StateInsert => write!(f, "state-insert"),
+            Reason::StateLimit => write!(f, "state-limit"),
+            Reason::SrcLimit => write!(f, "src-limit"),
+            Reason::Synproxy => write!(f, "synproxy"),
+            Reason::MapFailed => write!(f, "map-failed"),
+        }
+    }
+}
+
+///Reasons in pf's `PFRES_*` order, the index is the stable code of [`Reason::as_u8`].
+const REASON_CODES: [Reason; 16] = [
+    Reason::Match,
+    Reason::BadOffset,
+    Reason::Fragment,
+    Reason::Short,
+    Reason::Normalize,
+    Reason::Memory,
+    Reason::BadTimestamp,
+    Reason::Congestion,
+    Reason::IpOption,
+    Reason::ProtoCksum,
+    Reason::StateMismatch,
+    Reason::StateInsert,
+    Reason::StateLimit,
+    Reason::SrcLimit,
+    Reason::Synproxy,
+    Reason::MapFailed,
+];
+
+impl Reason {
+    /// Returns a stable numeric code for columnar storage, pf's own reason number
+    /// (`Match` is 0, `MapFailed` is 15).
+    pub fn as_u8(&self) -> u8 {
+        //Every variant is in the table, which is shorter than u8::MAX
+        REASON_CODES
+            .iter()
+            .position(|reason| reason == self)
+            .unwrap() as u8
+    }
+
+    /// Returns the reason with the code given by [`Reason::as_u8`].
+    pub fn from_u8(code: u8) -> Option<Self> {
+        REASON_CODES.get(usize::from(code)).cloned()
+    }
+}
+
+pub(crate) fn parse_reason(input: &str) -> IResult<&str, Reason> {
+    let (next, reason) = terminated(take_till(|c| c == ','), peek(char(',')))(input)?;
 
     if let Ok(reason) = Reason::from_str(reason) {
         Ok((next, reason))
@@ -79,12 +198,68 @@ fn parse_reason(input: &str) -> IResult<&str, Reason> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+///A semantic classification of why a packet was blocked or rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BlockCause {
+    /// A block/reject rule matched, including default deny rules.
+    RuleMatch,
+    /// pf ran out of a resource: memory, state table or source limits, congestion.
+    ResourceExhaustion,
+    /// The packet didn't match the state it belongs to.
+    StateMismatch,
+    /// The packet was malformed: bad offsets, fragments, short headers, checksums, options.
+    MalformedPacket,
+    /// Any other reason (synproxy, address mapping failures).
+    Other,
+}
+
+impl From<&Reason> for BlockCause {
+    fn from(reason: &Reason) -> Self {
+        match reason {
+            Reason::Match => BlockCause::RuleMatch,
+            Reason::Memory
+            | Reason::Congestion
+            | Reason::StateInsert
+            | Reason::StateLimit
+            | Reason::SrcLimit => BlockCause::ResourceExhaustion,
+            Reason::StateMismatch => BlockCause::StateMismatch,
+            Reason::BadOffset
+            | Reason::Fragment
+            | Reason::Short
+            | Reason::Normalize
+            | Reason::BadTimestamp
+            | Reason::IpOption
+            | Reason::ProtoCksum => BlockCause::MalformedPacket,
+            Reason::Synproxy | Reason::MapFailed => BlockCause::Other,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Action {
     Pass,
     Block,
     Reject,
+    /// The packet matched a logged NAT rule.
+    Nat,
+    /// The packet matched a logged redirect (port forward) rule.
+    Rdr,
+    /// The packet matched a logged bidirectional NAT rule.
+    Binat,
+}
+
+///The three-way bucket of an [`Action`], as returned by [`Action::disposition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Disposition {
+    /// The packet was passed.
+    Allow,
+    /// The packet was blocked or rejected.
+    Deny,
+    /// The packet had its addresses or ports rewritten by a NAT, redirect or binat rule.
+    Translate,
 }
 
 impl FromStr for Action {
@@ -94,14 +269,125 @@ impl FromStr for Action {
             "pass" => Ok(Action::Pass),
             "block" => Ok(Action::Block),
             "reject" => Ok(Action::Reject),
+            "nat" => Ok(Action::Nat),
+            "rdr" => Ok(Action::Rdr),
+            "binat" => Ok(Action::Binat),
             _ => Err(()),
         }
     }
 }
 
-fn parse_action(input: &str) -> IResult<&str, Action> {
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Pass => write!(f, "pass"),
+            Action::Block => write!(f, "block"),
+            Action::Reject => write!(f, "reject"),
+            Action::Nat => write!(f, "nat"),
+            Action::Rdr => write!(f, "rdr"),
+            Action::Binat => write!(f, "binat"),
+        }
+    }
+}
+
+impl Action {
+    /// Returns a stable numeric code for columnar storage: `Pass` is 0, `Block` is 1, `Reject` is 2,
+    /// then `Nat`, `Rdr` and `Binat` from 3 to 5.
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Action::Pass => 0,
+            Action::Block => 1,
+            Action::Reject => 2,
+            Action::Nat => 3,
+            Action::Rdr => 4,
+            Action::Binat => 5,
+        }
+    }
+
+    /// Returns the action with the code given by [`Action::as_u8`].
+    pub fn from_u8(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Action::Pass),
+            1 => Some(Action::Block),
+            2 => Some(Action::Reject),
+            3 => Some(Action::Nat),
+            4 => Some(Action::Rdr),
+            5 => Some(Action::Binat),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the packet was denied, either blocked or rejected.
+    pub fn is_deny(&self) -> bool {
+        self.disposition() == Disposition::Deny
+    }
+
+    /// Returns `true` if the packet was allowed through.
+    pub fn is_allow(&self) -> bool {
+        self.disposition() == Disposition::Allow
+    }
+
+    /// Buckets the action into allow, deny or translate.
+    pub fn disposition(&self) -> Disposition {
+        match self {
+            Action::Pass => Disposition::Allow,
+            Action::Block | Action::Reject => Disposition::Deny,
+            Action::Nat | Action::Rdr | Action::Binat => Disposition::Translate,
+        }
+    }
+}
+
+///How a `reject` rule refused the packet, when the log notes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RejectMethod {
+    /// A TCP RST was returned.
+    Rst,
+    /// An ICMP (or ICMPv6) unreachable was returned.
+    Icmp,
+    /// A method the parser doesn't recognize.
+    Unknown,
+}
+
+impl FromStr for RejectMethod {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rst" => Ok(RejectMethod::Rst),
+            "icmp" | "icmp6" => Ok(RejectMethod::Icmp),
+            _ => Ok(RejectMethod::Unknown),
+        }
+    }
+}
+
+impl Display for RejectMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RejectMethod::Rst => write!(f, "rst"),
+            RejectMethod::Icmp => write!(f, "icmp"),
+            RejectMethod::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+///Parses the reject method column some logs add between a `reject` action and the direction.
+fn parse_reject_method(input: &str) -> IResult<&str, RejectMethod> {
+    terminated(csv(take_till1(|c| c == ',')), peek(parse_dir))
+        //Infallible, unknown methods become Unknown
+        .map(|method| RejectMethod::from_str(method).unwrap())
+        .parse(input)
+}
+
+pub(crate) fn parse_action(input: &str) -> IResult<&str, Action> {
     let (next, action) = terminated(
-        alt((tag("pass"), tag("block"), tag("reject"))),
+        alt((
+            tag("pass"),
+            tag("block"),
+            tag("reject"),
+            tag("nat"),
+            tag("rdr"),
+            tag("binat"),
+        )),
         peek(char(',')),
     )(input)?;
 
@@ -118,23 +404,129 @@ pub struct RuleInfo {
     pub number: u32,
     pub subrulenr: Option<u32>,
     pub anchorname: Option<String>,
+    /// The rule label, empty for dialects that don't log it.
     pub label: String,
+    /// The numeric rule identifier logged by pfSense in place of the label.
+    pub ridentifier: Option<u64>,
+    /// The tracker ID logged as the first column by the [`Dialect::Tracker`] layout.
+    pub tracker: Option<u64>,
+}
+
+impl RuleInfo {
+    /// Returns the rule identifier as `number`, or `number.subrulenr` when the subrule is present.
+    pub fn rule_id(&self) -> String {
+        match self.subrulenr {
+            Some(subrulenr) => format!("{}.{}", self.number, subrulenr),
+            None => self.number.to_string(),
+        }
+    }
+
+    /// Returns the label as a number when it's a numeric rule ID, `None` otherwise.
+    pub fn label_as_u64(&self) -> Option<u64> {
+        match self.label.bytes().all(|b| b.is_ascii_digit()) {
+            true => self.label.parse().ok(),
+            false => None,
+        }
+    }
+
+    /// Returns the label as a [`RuleUuid`] when it's a UUID in the canonical
+    /// `8-4-4-4-12` hexadecimal form, `None` otherwise.
+    ///
+    /// The label string stays the source of truth.
+    pub fn label_uuid(&self) -> Option<RuleUuid> {
+        let groups: Vec<&str> = self.label.split('-').collect();
+        let canonical = groups.len() == 5
+            && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+                group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit())
+            });
+
+        match canonical {
+            true => u128::from_str_radix(&groups.concat(), 16)
+                .ok()
+                .map(RuleUuid),
+            false => None,
+        }
+    }
 }
 
-fn parse_rule_info(input: &str) -> IResult<&str, RuleInfo> {
-    let (next, rulenr) = csv(parse_u32)(input)?;
-    let (next, subrulenr) = csv(opt(parse_u32))(next)?;
-    let (next, anchorname) = csv(opt(alphanumeric1.map(|s: &str| s.into())))(next)?;
-    let (next, label) = csv(alphanumeric1).map(|s| s.into()).parse(next)?;
+/// A rule UUID read from the label, see [`RuleInfo::label_uuid`].
+///
+/// Displayed in the lowercase hyphenated form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RuleUuid(u128);
 
-    let rule_info = RuleInfo {
-        number: rulenr,
+impl RuleUuid {
+    /// Returns the UUID as a 128-bit value, which converts losslessly with `uuid::Uuid::from_u128`.
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+}
+
+impl Display for RuleUuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+}
+
+///Parses the subrule number and anchor columns that follow the first column.
+fn parse_subrule_anchor(input: &str) -> IResult<&str, (Option<u32>, Option<String>)> {
+    let (next, subrulenr) = csv(opt(parse_u32))(input)?;
+    //Anchors are paths such as "userrules/foo"
+    let (next, anchorname) =
+        csv(opt_field(take_till1(|c| c == ',').map(|s: &str| s.into())))(next)?;
+
+    Ok((next, (subrulenr, anchorname)))
+}
+
+pub(crate) fn parse_rule_info(input: &str, dialect: Dialect) -> IResult<&str, RuleInfo> {
+    let rule_info = |number, (subrulenr, anchorname), label, ridentifier, tracker| RuleInfo {
+        number,
         subrulenr,
         anchorname,
         label,
+        ridentifier,
+        tracker,
     };
 
-    Ok((next, rule_info))
+    match dialect {
+        Dialect::OpnSense => {
+            let (next, rulenr) = csv(parse_u32)(input)?;
+            let (next, subrule_anchor) = parse_subrule_anchor(next)?;
+            //Rules without a label log an empty column
+            let (next, label) = csv(parse_utf8_string)(next)?;
+            Ok((next, rule_info(rulenr, subrule_anchor, label, None, None)))
+        }
+        Dialect::PfSense => {
+            let (next, rulenr) = csv(parse_u32)(input)?;
+            let (next, subrule_anchor) = parse_subrule_anchor(next)?;
+            let (next, ridentifier) = csv(parse_u64)(next)?;
+            let rule_info = rule_info(
+                rulenr,
+                subrule_anchor,
+                String::new(),
+                Some(ridentifier),
+                None,
+            );
+            Ok((next, rule_info))
+        }
+        Dialect::Tracker => {
+            //The tracker is logged first and the rule number in place of the label
+            let (next, tracker) = csv(parse_u64)(input)?;
+            let (next, subrule_anchor) = parse_subrule_anchor(next)?;
+            let (next, rulenr) = csv(parse_u32)(next)?;
+            let rule_info = rule_info(rulenr, subrule_anchor, String::new(), None, Some(tracker));
+            Ok((next, rule_info))
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -144,14 +536,118 @@ pub struct PacketFilter {
     pub interface: String,
     pub reason: Reason,
     pub action: Action,
+    /// How a rejected packet was refused, when logged after the `reject` action.
+    pub reject_method: Option<RejectMethod>,
     pub dir: Dir,
 }
 
-pub(crate) fn parse_packet_filter(input: &str) -> IResult<&str, PacketFilter> {
-    let (next, rule_info) = parse_rule_info(input)?;
+///An interface split into its device name and the optional friendly description.
+///
+/// Some setups log the interface as `igb0 (WAN)`; `Display` reproduces that form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Interface {
+    pub device: String,
+    pub description: Option<String>,
+}
+
+impl FromStr for Interface {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let described =
+            s.strip_suffix(')')
+                .and_then(|s| s.split_once(" ("))
+                .map(|(device, description)| Interface {
+                    device: device.into(),
+                    description: Some(description.into()),
+                });
+
+        Ok(described.unwrap_or_else(|| Interface {
+            device: s.into(),
+            description: None,
+        }))
+    }
+}
+
+impl Display for Interface {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.description {
+            Some(description) => write!(f, "{} ({})", self.device, description),
+            None => write!(f, "{}", self.device),
+        }
+    }
+}
+
+///A set of interfaces known in advance, registered by the user.
+///
+/// Implement it on an enum listing the interfaces of a deployment to get
+/// exhaustive `match`es over [`TypedInterface`].
+pub trait KnownInterface: Sized {
+    /// Maps a raw interface name (e.g. `vlan0.20`) to a known interface, if any.
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+///An interface mapped to a user registered [`KnownInterface`], or the raw name when it isn't known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TypedInterface<K> {
+    Known(K),
+    Other(String),
+}
+
+impl<K: KnownInterface> TypedInterface<K> {
+    pub fn from_name(name: &str) -> Self {
+        match K::from_name(name) {
+            Some(known) => TypedInterface::Known(known),
+            None => TypedInterface::Other(name.into()),
+        }
+    }
+}
+
+impl PacketFilter {
+    /// Returns the interface name lowercased, a canonical form for joining
+    /// logs of firewalls that log the same interface with different casing.
+    pub fn normalized_interface(&self) -> String {
+        self.interface.to_lowercase()
+    }
+
+    /// Classifies why the packet was blocked, `None` when it was passed.
+    pub fn block_cause(&self) -> Option<BlockCause> {
+        match self.action {
+            Action::Block | Action::Reject => Some(BlockCause::from(&self.reason)),
+            Action::Pass | Action::Nat | Action::Rdr | Action::Binat => None,
+        }
+    }
+
+    /// Splits the logged interface into device and description.
+    pub fn parsed_interface(&self) -> Interface {
+        //Infallible, every string is at least a device name
+        Interface::from_str(&self.interface).unwrap()
+    }
+
+    /// Maps the logged interface to the user registered set `K`.
+    pub fn typed_interface<K: KnownInterface>(&self) -> TypedInterface<K> {
+        TypedInterface::from_name(&self.interface)
+    }
+}
+
+pub(crate) fn parse_packet_filter<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, PacketFilter> {
+    let (next, rule_info) = parse_rule_info(input, options.dialect)?;
     let (next, interface) = csv(parse_utf8_string)(next)?;
-    let (next, reason) = csv(parse_reason)(next)?;
+    let (next, reason) = match options.optional_reason {
+        //Older pf builds omit the reason column, going straight to the action
+        true => alt((csv(parse_reason), peek(parse_action).map(|_| Reason::Match)))(next)?,
+        false => csv(parse_reason)(next)?,
+    };
     let (next, action) = csv(parse_action).parse(next)?;
+    let (next, reject_method) = match action {
+        Action::Reject => opt(parse_reject_method)(next)?,
+        _ => (next, None),
+    };
     let (next, dir) = csv(parse_dir)(next)?;
 
     Ok((
@@ -161,6 +657,7 @@ pub(crate) fn parse_packet_filter(input: &str) -> IResult<&str, PacketFilter> {
             interface,
             reason,
             action,
+            reject_method,
             dir,
         },
     ))
@@ -195,6 +692,12 @@ mod test {
         let match_reason = parse_reason("match,");
         assert_eq!(Ok((",", Reason::Match)), match_reason);
 
+        let state_limit = parse_reason("state-limit,");
+        assert_eq!(Ok((",", Reason::StateLimit)), state_limit);
+
+        let proto_cksum = parse_reason("proto-cksum,");
+        assert_eq!(Ok((",", Reason::ProtoCksum)), proto_cksum);
+
         let fail_reason = parse_reason("matcha,");
         assert!(fail_reason.is_err());
 
@@ -222,14 +725,102 @@ mod test {
         let action_block_fail = parse_action("blocked,");
         assert!(action_block_fail.is_err());
 
+        assert_eq!(Ok((",", Action::Rdr)), parse_action("rdr,"));
+        assert_eq!(Ok((",", Action::Binat)), parse_action("binat,"));
+
         let actio_wrong_fail = parse_action("wrong,");
         assert!(actio_wrong_fail.is_err())
     }
 
+    #[test]
+    fn test_u8_codes() {
+        for code in 0..6 {
+            assert_eq!(code, Action::from_u8(code).unwrap().as_u8());
+        }
+        assert_eq!(2, Action::Reject.as_u8());
+        assert_eq!(None, Action::from_u8(6));
+
+        for dir in [Dir::In, Dir::Out] {
+            assert_eq!(Some(dir.clone()), Dir::from_u8(dir.as_u8()));
+        }
+        assert_eq!(None, Dir::from_u8(2));
+
+        for code in 0..16 {
+            assert_eq!(code, Reason::from_u8(code).unwrap().as_u8());
+        }
+        assert_eq!(0, Reason::Match.as_u8());
+        assert_eq!(12, Reason::StateLimit.as_u8());
+        assert_eq!(None, Reason::from_u8(16));
+    }
+
+    #[test]
+    fn test_action_grouping() {
+        assert!(Action::Pass.is_allow());
+        assert!(!Action::Pass.is_deny());
+        assert!(Action::Block.is_deny());
+        assert!(Action::Reject.is_deny());
+        assert!(!Action::Reject.is_allow());
+        assert!(!Action::Nat.is_allow());
+        assert!(!Action::Nat.is_deny());
+    }
+
+    #[test]
+    fn test_disposition() {
+        assert_eq!(Disposition::Allow, Action::Pass.disposition());
+        assert_eq!(Disposition::Deny, Action::Block.disposition());
+        assert_eq!(Disposition::Deny, Action::Reject.disposition());
+        assert_eq!(Disposition::Translate, Action::Nat.disposition());
+        assert_eq!(Disposition::Translate, Action::Rdr.disposition());
+        assert_eq!(Disposition::Translate, Action::Binat.disposition());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("in", Dir::In.to_string());
+        assert_eq!("out", Dir::Out.to_string());
+        assert_eq!("pass", Action::Pass.to_string());
+        assert_eq!("block", Action::Block.to_string());
+        assert_eq!("reject", Action::Reject.to_string());
+    }
+
+    #[test]
+    fn test_reason_display() {
+        assert_eq!("match", Reason::Match.to_string());
+        assert_eq!("state-limit", Reason::StateLimit.to_string());
+        assert_eq!("map-failed", Reason::MapFailed.to_string());
+    }
+
+    #[test]
+    fn test_block_cause() {
+        let (_, mut packet_filter) = parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,state-limit,block,in,other,...",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(Reason::StateLimit, packet_filter.reason);
+        assert_eq!(
+            Some(BlockCause::ResourceExhaustion),
+            packet_filter.block_cause()
+        );
+
+        packet_filter.reason = Reason::Match;
+        packet_filter.action = Action::Reject;
+        assert_eq!(Some(BlockCause::RuleMatch), packet_filter.block_cause());
+
+        packet_filter.reason = Reason::BadOffset;
+        assert_eq!(
+            Some(BlockCause::MalformedPacket),
+            packet_filter.block_cause()
+        );
+
+        packet_filter.action = Action::Pass;
+        assert_eq!(None, packet_filter.block_cause());
+    }
+
     #[test]
     fn test_parse_rule() {
         let rule_info = "15,,,fae559338f65e11c53669fc3642c93c2,";
-        let parsed = parse_rule_info(rule_info);
+        let parsed = parse_rule_info(rule_info, Dialect::OpnSense);
 
         assert_eq!(
             Ok((
@@ -239,16 +830,274 @@ mod test {
                     subrulenr: None,
                     anchorname: None,
                     label: "fae559338f65e11c53669fc3642c93c2".into(),
+                    ridentifier: None,
+                    tracker: None,
                 }
             )),
             parsed
         )
     }
 
+    #[test]
+    fn test_parse_rule_empty_label() {
+        assert_eq!(
+            Ok((
+                "vlan0.20,",
+                RuleInfo {
+                    number: 15,
+                    subrulenr: None,
+                    anchorname: None,
+                    label: String::new(),
+                    ridentifier: None,
+                    tracker: None,
+                }
+            )),
+            parse_rule_info("15,,,,vlan0.20,", Dialect::OpnSense)
+        );
+    }
+
+    #[test]
+    fn test_rule_id() {
+        let (_, rule_info) = parse_rule_info("96,,,fae559338f65e11c,", Dialect::OpnSense).unwrap();
+        assert_eq!("96", rule_info.rule_id());
+
+        let (_, rule_info) = parse_rule_info("96,1,,fae559338f65e11c,", Dialect::OpnSense).unwrap();
+        assert_eq!("96.1", rule_info.rule_id());
+    }
+
+    #[test]
+    fn test_label_as_u64() {
+        let (_, rule_info) = parse_rule_info("15,,,1000000103,", Dialect::OpnSense).unwrap();
+        assert_eq!(Some(1000000103), rule_info.label_as_u64());
+
+        let (_, rule_info) = parse_rule_info("15,,,fae559338f65e11c,", Dialect::OpnSense).unwrap();
+        assert_eq!(None, rule_info.label_as_u64());
+
+        let (_, rule_info) = parse_rule_info("15,,,+1,", Dialect::OpnSense).unwrap();
+        assert_eq!(None, rule_info.label_as_u64());
+
+        let (_, rule_info) = parse_rule_info("15,,,,", Dialect::OpnSense).unwrap();
+        assert_eq!(None, rule_info.label_as_u64());
+    }
+
+    #[test]
+    fn test_label_uuid() {
+        let (_, rule_info) = parse_rule_info(
+            "15,,,6c0b8f8e-3c6a-4f4a-9d1b-2a7e5c3d9f01,",
+            Dialect::OpnSense,
+        )
+        .unwrap();
+        let uuid = rule_info.label_uuid().unwrap();
+        assert_eq!(0x6c0b8f8e_3c6a_4f4a_9d1b_2a7e5c3d9f01, uuid.as_u128());
+        assert_eq!("6c0b8f8e-3c6a-4f4a-9d1b-2a7e5c3d9f01", uuid.to_string());
+
+        let (_, rule_info) = parse_rule_info(
+            "15,,,6C0B8F8E-3C6A-4F4A-9D1B-2A7E5C3D9F01,",
+            Dialect::OpnSense,
+        )
+        .unwrap();
+        assert_eq!(Some(uuid), rule_info.label_uuid());
+
+        for label in [
+            "fae559338f65e11c53669fc3642c93c2",
+            "6c0b8f8e-3c6a-4f4a-9d1b-2a7e5c3d9f0",
+            "6c0b8f8e-3c6a-4f4a-9d1b-2a7e5c3d9f0g",
+            "+c0b8f8e-3c6a-4f4a-9d1b-2a7e5c3d9f01",
+        ] {
+            let (_, rule_info) =
+                parse_rule_info(&format!("15,,,{},", label), Dialect::OpnSense).unwrap();
+            assert_eq!(None, rule_info.label_uuid(), "{}", label);
+        }
+    }
+
+    #[test]
+    fn test_parse_reject_method() {
+        let (next, packet_filter) = parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,igb0,match,reject,rst,in,4,",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!("4,", next);
+        assert_eq!(Action::Reject, packet_filter.action);
+        assert_eq!(Some(RejectMethod::Rst), packet_filter.reject_method);
+        assert_eq!(Dir::In, packet_filter.dir);
+
+        let (_, packet_filter) = parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,igb0,match,reject,icmp6,out,6,",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(Some(RejectMethod::Icmp), packet_filter.reject_method);
+
+        let (_, packet_filter) = parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,igb0,match,reject,in,4,",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(None, packet_filter.reject_method);
+
+        //Only a reject carries a method
+        assert!(parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,igb0,match,block,rst,in,4,",
+            &ParseOptions::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_rule_tracker() {
+        let parsed = parse_rule_info("1700000000123,,,96,", Dialect::Tracker);
+        assert_eq!(
+            Ok((
+                "",
+                RuleInfo {
+                    number: 96,
+                    subrulenr: None,
+                    anchorname: None,
+                    label: String::new(),
+                    ridentifier: None,
+                    tracker: Some(1700000000123),
+                }
+            )),
+            parsed
+        );
+
+        //The tracker overflows the rule number of the other layouts
+        assert!(parse_rule_info("1700000000123,,,96,", Dialect::PfSense).is_err());
+    }
+
+    #[test]
+    fn test_parse_rule_pfsense() {
+        let rule_info = "5,,,1000000103,";
+        let parsed = parse_rule_info(rule_info, Dialect::PfSense);
+
+        assert_eq!(
+            Ok((
+                "",
+                RuleInfo {
+                    number: 5,
+                    subrulenr: None,
+                    anchorname: None,
+                    label: String::new(),
+                    ridentifier: Some(1000000103),
+                    tracker: None,
+                }
+            )),
+            parsed
+        );
+
+        assert!(
+            parse_rule_info("15,,,fae559338f65e11c53669fc3642c93c2,", Dialect::PfSense).is_err()
+        );
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Site {
+        Wan,
+        Lan,
+    }
+
+    impl KnownInterface for Site {
+        fn from_name(name: &str) -> Option<Self> {
+            match name {
+                "igb0" => Some(Site::Wan),
+                "vlan0.20" => Some(Site::Lan),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_packet_filter_without_reason() {
+        let without_reason = "15,,,fae559338f65e11c53669fc3642c93c2,em0,block,in,4,";
+        assert!(parse_packet_filter(without_reason, &ParseOptions::default()).is_err());
+
+        let options = ParseOptions {
+            optional_reason: true,
+            ..Default::default()
+        };
+        let (next, packet_filter) = parse_packet_filter(without_reason, &options).unwrap();
+        assert_eq!("4,", next);
+        assert_eq!(Reason::Match, packet_filter.reason);
+        assert_eq!(Action::Block, packet_filter.action);
+        assert_eq!(Dir::In, packet_filter.dir);
+
+        assert!(parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,em0,unknown,block,in,4,",
+            &ParseOptions::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_normalized_interface() {
+        let (_, packet_filter) = parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,EM0,match,block,in,other,...",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!("EM0", packet_filter.interface);
+        assert_eq!("em0", packet_filter.normalized_interface());
+    }
+
+    #[test]
+    fn test_typed_interface() {
+        assert_eq!(
+            TypedInterface::Known(Site::Wan),
+            TypedInterface::<Site>::from_name("igb0")
+        );
+        assert_eq!(
+            TypedInterface::Other("igb1".into()),
+            TypedInterface::<Site>::from_name("igb1")
+        );
+
+        let (_, packet_filter) = parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,block,in,other,...",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            TypedInterface::Known(Site::Lan),
+            packet_filter.typed_interface::<Site>()
+        );
+    }
+
+    #[test]
+    fn test_interface() {
+        let described = Interface::from_str("igb0 (WAN)").unwrap();
+        assert_eq!(
+            Interface {
+                device: "igb0".into(),
+                description: Some("WAN".into()),
+            },
+            described
+        );
+        assert_eq!("igb0 (WAN)", described.to_string());
+
+        let plain = Interface::from_str("vlan0.20").unwrap();
+        assert_eq!(
+            Interface {
+                device: "vlan0.20".into(),
+                description: None,
+            },
+            plain
+        );
+        assert_eq!("vlan0.20", plain.to_string());
+
+        let (_, packet_filter) = parse_packet_filter(
+            "15,,,fae559338f65e11c53669fc3642c93c2,igb0 (WAN),match,block,in,other,...",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!("igb0 (WAN)", packet_filter.interface);
+        assert_eq!(described, packet_filter.parsed_interface());
+    }
+
     #[test]
     fn test_parse_packet_filter() {
         let packet_filter = parse_packet_filter(
             "15,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,block,in,other,...",
+            &ParseOptions::default(),
         );
         assert_eq!(
             Ok((
@@ -258,11 +1107,14 @@ mod test {
                         number: 15,
                         subrulenr: None,
                         anchorname: None,
-                        label: "fae559338f65e11c53669fc3642c93c2".into()
+                        label: "fae559338f65e11c53669fc3642c93c2".into(),
+                        ridentifier: None,
+                        tracker: None,
                     },
                     interface: "vlan0.20".into(),
                     reason: Reason::Match,
                     action: Action::Block,
+                    reject_method: None,
                     dir: Dir::In
                 }
             )),
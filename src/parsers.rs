@@ -0,0 +1,130 @@
+//! The low-level [nom](https://docs.rs/nom/7) parsers used by [`parse_log`](crate::parse_log).
+//!
+//! Each parser consumes a part of a log line and returns the remaining input,
+//! so they can be composed with custom combinators.
+//! Most of them expect the trailing comma of their last column to be present.
+//!
+//! This is a lower-level API than [`parse_log`](crate::parse_log) and is less stable:
+//! signatures may change along with the log format or the nom version.
+//!
+//! # Example
+//! ```rust
+//! use senpa::parsers::{parse_ip_header, parse_packet_filter};
+//! use senpa::{Action, ParseOptions};
+//!
+//! let log = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+//! 4,0x0,,127,58940,0,none,17,udp,\
+//! 106,192.168.10.15,192.168.20.11,49678,161,86";
+//!
+//! let (next, packet_filter) = parse_packet_filter(log, &ParseOptions::default()).unwrap();
+//! let (next, (protocol, _)) = parse_ip_header(next).unwrap();
+//! assert_eq!(Action::Pass, packet_filter.action);
+//! assert_eq!(17, protocol.num);
+//! assert_eq!("106,192.168.10.15,192.168.20.11,49678,161,86", next);
+//! ```
+
+use std::net::IpAddr;
+
+use nom::IResult;
+
+use crate::ip::{self, IpData, IpSpecific};
+use crate::options::{Dialect, ParseOptions};
+use crate::packet_filter::{self, Action, Dir, PacketFilter, Reason, RuleInfo};
+use crate::protocol::{self, Ports, ProtoInfo, ProtoName, Protocol};
+
+/// Parses an IPv4 or IPv6 address.
+pub fn parse_ip_addr(input: &str) -> IResult<&str, IpAddr> {
+    ip::parse_ip_addr(input)
+}
+
+/// Parses the length and addresses following the IP header.
+pub fn parse_ip_data<'a>(input: &'a str, specific: &IpSpecific) -> IResult<&'a str, IpData> {
+    ip::parse_ip_data(input, specific)
+}
+
+/// Parses the version-specific IP header, up to and including the protocol columns.
+pub fn parse_ip_header(input: &str) -> IResult<&str, (Protocol, IpSpecific)> {
+    ip::parse_ip_header(input)
+}
+
+/// Parses the header of an encapsulated packet when the outer protocol is a tunnel (IP-in-IP or 6in4).
+///
+/// The inner header is optional: tunnel logs that don't carry it leave the input untouched.
+pub fn parse_inner_ip_header<'a>(
+    input: &'a str,
+    outer: &Protocol,
+) -> IResult<&'a str, Option<(Protocol, IpSpecific)>> {
+    ip::parse_inner_ip_header(input, outer)
+}
+
+/// Parses the rule number, subrule, anchor and dialect-specific fourth column.
+pub fn parse_rule_info(input: &str, dialect: Dialect) -> IResult<&str, RuleInfo> {
+    packet_filter::parse_rule_info(input, dialect)
+}
+
+/// Parses the reason column.
+pub fn parse_reason(input: &str) -> IResult<&str, Reason> {
+    packet_filter::parse_reason(input)
+}
+
+/// Parses the action column.
+pub fn parse_action(input: &str) -> IResult<&str, Action> {
+    packet_filter::parse_action(input)
+}
+
+/// Parses the direction column.
+pub fn parse_dir(input: &str) -> IResult<&str, Dir> {
+    packet_filter::parse_dir(input)
+}
+
+/// Parses the packet filter columns, from the rule number to the direction.
+pub fn parse_packet_filter<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, PacketFilter> {
+    packet_filter::parse_packet_filter(input, options)
+}
+
+/// Parses the source and destination port columns.
+pub fn parse_src_dst_ports(input: &str) -> IResult<&str, Ports> {
+    protocol::parse_src_dst_ports(input)
+}
+
+/// Parses the protocol-specific columns of `proto`.
+pub fn parse_proto_info<'a>(
+    input: &'a str,
+    proto: &ProtoName,
+    options: &ParseOptions,
+) -> IResult<&'a str, ProtoInfo> {
+    protocol::parse_proto_info(input, proto, options)
+}
+
+/// Parses the TCP columns.
+pub fn parse_tcp_info<'a>(input: &'a str, options: &ParseOptions) -> IResult<&'a str, ProtoInfo> {
+    protocol::parse_tcp_info(input, options)
+}
+
+/// Parses the UDP columns.
+pub fn parse_udp_info(input: &str) -> IResult<&str, ProtoInfo> {
+    protocol::parse_udp_info(input)
+}
+
+/// Parses the ICMP columns.
+pub fn parse_icmp_info(input: &str) -> IResult<&str, ProtoInfo> {
+    protocol::parse_icmp_info(input)
+}
+
+/// Parses the VRRP columns.
+pub fn parse_vrrp_info(input: &str) -> IResult<&str, ProtoInfo> {
+    protocol::parse_vrrp_info(input)
+}
+
+/// Parses the PIM columns.
+pub fn parse_pim_info(input: &str) -> IResult<&str, ProtoInfo> {
+    protocol::parse_pim_info(input)
+}
+
+/// Parses the DCCP columns.
+pub fn parse_dccp_info(input: &str) -> IResult<&str, ProtoInfo> {
+    protocol::parse_dccp_info(input)
+}
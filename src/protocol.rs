@@ -1,34 +1,84 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::net::IpAddr;
 use std::str::FromStr;
 
 use nom::bytes::complete::take_till;
-use nom::character::complete::{u16 as parse_u16, u32 as parse_u32};
+use nom::character::complete::multispace0;
+use nom::character::complete::{char, u16 as parse_u16, u32 as parse_u32, u8 as parse_u8};
 use nom::combinator::rest;
-use nom::sequence::terminated;
+use nom::sequence::{pair, preceded, terminated, tuple};
 use nom::Parser;
 use nom::{
-    combinator::{eof, opt},
+    combinator::{eof, map_res, opt},
     IResult,
 };
 
-use crate::utils::{csv, parse_utf8_string};
+use crate::ip::parse_ip_addr;
+use crate::options::ParseOptions;
+use crate::utils::{csv, opt_field, parse_utf8_string};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-
 pub struct Protocol {
     pub num: u8, //protonum
     pub name: ProtoName,
 }
 
+/// Reserved IANA protocol number, used for protocol names missing from [`IANA_PROTOCOLS`].
+pub const RESERVED_PROTO_NUM: u8 = 255;
+
+/// IANA protocol numbers of the protocol names logged by pf.
+const IANA_PROTOCOLS: &[(u8, &str)] = &[
+    (1, "icmp"),
+    (2, "igmp"),
+    (4, "ipencap"),
+    (6, "tcp"),
+    (17, "udp"),
+    (33, "dccp"),
+    (41, "ipv6"),
+    (47, "gre"),
+    (50, "esp"),
+    (51, "ah"),
+    (58, "ipv6-icmp"),
+    (89, "ospf"),
+    (103, "pim"),
+    //CARP shares VRRP's number; a bare 112 is read as VRRP, whose payload parser is structured
+    (112, "vrrp"),
+    (112, "carp"),
+    (132, "sctp"),
+];
+
+impl Protocol {
+    /// Builds a `Protocol` from its name, filling the number from the IANA table.
+    ///
+    /// Names missing from the table get [`RESERVED_PROTO_NUM`].
+    pub fn from_name(name: ProtoName) -> Protocol {
+        let num = name.num().unwrap_or(RESERVED_PROTO_NUM);
+        Protocol { num, name }
+    }
+
+    pub fn tcp() -> Protocol {
+        Protocol::from_name(ProtoName::Tcp)
+    }
+
+    pub fn udp() -> Protocol {
+        Protocol::from_name(ProtoName::Udp)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-
 pub enum ProtoName {
     Tcp,
     Udp,
+    Icmp,
+    Vrrp,
+    Pim,
+    Dccp,
     Other(String),
 }
 
@@ -39,11 +89,56 @@ impl FromStr for ProtoName {
         match s {
             "udp" => Ok(ProtoName::Udp),
             "tcp" => Ok(ProtoName::Tcp),
+            "icmp" => Ok(ProtoName::Icmp),
+            "vrrp" => Ok(ProtoName::Vrrp),
+            "pim" => Ok(ProtoName::Pim),
+            "dccp" => Ok(ProtoName::Dccp),
+            //Some firmware logs the protocol number in place of the name
+            numeric if !numeric.is_empty() && numeric.bytes().all(|b| b.is_ascii_digit()) => {
+                match numeric.parse::<u8>() {
+                    Ok(num) => Ok(ProtoName::from_num(num)),
+                    Err(_) => Ok(ProtoName::Other(numeric.into())),
+                }
+            }
             other => Ok(ProtoName::Other(other.into())),
         }
     }
 }
 
+impl ProtoName {
+    /// Returns the IANA protocol number of this protocol, if known.
+    pub fn num(&self) -> Option<u8> {
+        let name = self.to_string();
+        IANA_PROTOCOLS
+            .iter()
+            .find(|(_, known)| *known == name)
+            .map(|(num, _)| *num)
+    }
+
+    /// Returns the protocol with the given IANA number, `Other` holding the number when unknown.
+    pub fn from_num(num: u8) -> ProtoName {
+        match IANA_PROTOCOLS.iter().find(|(known, _)| *known == num) {
+            //Infallible, unknown names become Other
+            Some((_, name)) => ProtoName::from_str(name).unwrap(),
+            None => ProtoName::Other(num.to_string()),
+        }
+    }
+}
+
+impl Display for ProtoName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtoName::Tcp => write!(f, "tcp"),
+            ProtoName::Udp => write!(f, "udp"),
+            ProtoName::Icmp => write!(f, "icmp"),
+            ProtoName::Vrrp => write!(f, "vrrp"),
+            ProtoName::Pim => write!(f, "pim"),
+            ProtoName::Dccp => write!(f, "dccp"),
+            ProtoName::Other(other) => write!(f, "{}", other),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ports {
@@ -51,6 +146,53 @@ pub struct Ports {
     pub dstport: u16,
 }
 
+///Returns `true` if one of the two port columns at the start of `input` is a number too large for a port.
+pub(crate) fn has_port_out_of_range(input: &str) -> bool {
+    input.split(',').take(2).any(|port| {
+        !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) && port.parse::<u16>().is_err()
+    })
+}
+
+///Well-known ports and their service names, the same over TCP and UDP.
+const SERVICES: [(u16, &str); 24] = [
+    (20, "ftp-data"),
+    (21, "ftp"),
+    (22, "ssh"),
+    (23, "telnet"),
+    (25, "smtp"),
+    (53, "dns"),
+    (67, "dhcp"),
+    (68, "dhcp"),
+    (69, "tftp"),
+    (80, "http"),
+    (110, "pop3"),
+    (123, "ntp"),
+    (143, "imap"),
+    (161, "snmp"),
+    (162, "snmptrap"),
+    (389, "ldap"),
+    (443, "https"),
+    (445, "smb"),
+    (514, "syslog"),
+    (993, "imaps"),
+    (995, "pop3s"),
+    (1194, "openvpn"),
+    (3389, "rdp"),
+    (5060, "sip"),
+];
+
+impl Ports {
+    /// Returns the service name of a well-known destination port (e.g. `443` is `https`).
+    ///
+    /// Services are named the same for TCP and UDP, so UDP/53 and TCP/53 are both `dns`.
+    pub fn dst_service(&self) -> Option<&'static str> {
+        SERVICES
+            .iter()
+            .find(|(port, _)| *port == self.dstport)
+            .map(|(_, service)| *service)
+    }
+}
+
 pub(crate) fn parse_src_dst_ports(input: &str) -> IResult<&str, Ports> {
     let (next, srcport) = csv(parse_u16)(input)?;
     let (next, dstport) = csv(parse_u16)(next)?;
@@ -58,6 +200,186 @@ pub(crate) fn parse_src_dst_ports(input: &str) -> IResult<&str, Ports> {
     Ok((next, Ports { srcport, dstport }))
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TcpOption {
+    Eol,
+    Nop,
+    Mss,
+    Wscale,
+    SackOk,
+    Sack,
+    Timestamp,
+    Md5,
+    Other(String),
+}
+
+impl FromStr for TcpOption {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eol" => Ok(TcpOption::Eol),
+            "nop" => Ok(TcpOption::Nop),
+            "mss" => Ok(TcpOption::Mss),
+            "wscale" => Ok(TcpOption::Wscale),
+            "sackOK" => Ok(TcpOption::SackOk),
+            "sack" => Ok(TcpOption::Sack),
+            "TS" => Ok(TcpOption::Timestamp),
+            "md5" => Ok(TcpOption::Md5),
+            other => Ok(TcpOption::Other(other.into())),
+        }
+    }
+}
+
+impl Display for TcpOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TcpOption::Eol => "eol",
+            TcpOption::Nop => "nop",
+            TcpOption::Mss => "mss",
+            TcpOption::Wscale => "wscale",
+            TcpOption::SackOk => "sackOK",
+            TcpOption::Sack => "sack",
+            TcpOption::Timestamp => "TS",
+            TcpOption::Md5 => "md5",
+            TcpOption::Other(other) => other,
+        };
+        f.write_str(name)
+    }
+}
+
+///The TCP options of a segment, in the order they were logged.
+///
+/// `Display` reproduces the semicolon-joined pf notation (`mss;nop;wscale`).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TcpOptions(pub Vec<TcpOption>);
+
+impl FromStr for TcpOptions {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(TcpOptions::default());
+        }
+
+        s.split(';')
+            .map(TcpOption::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map(TcpOptions)
+    }
+}
+
+impl Display for TcpOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, option) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(";")?;
+            }
+            write!(f, "{}", option)?;
+        }
+        Ok(())
+    }
+}
+
+///The TCP flags of a segment, decoded from the pf letters (`S`, `A`, `F`, `R`, `P`, `U`, `E`, `W`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TcpFlags {
+    pub syn: bool,
+    pub ack: bool,
+    pub fin: bool,
+    pub rst: bool,
+    pub psh: bool,
+    pub urg: bool,
+    pub ece: bool,
+    pub cwr: bool,
+}
+
+impl FromStr for TcpFlags {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut flags = TcpFlags::default();
+        for letter in s.chars() {
+            match letter {
+                'S' => flags.syn = true,
+                'A' => flags.ack = true,
+                'F' => flags.fin = true,
+                'R' => flags.rst = true,
+                'P' => flags.psh = true,
+                'U' => flags.urg = true,
+                'E' => flags.ece = true,
+                'W' => flags.cwr = true,
+                //Unknown letters are ignored, the raw string stays available
+                _ => {}
+            }
+        }
+        Ok(flags)
+    }
+}
+
+///A coarse connection state inferred from the TCP flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TcpStateHint {
+    /// SYN without ACK: a connection attempt.
+    ConnectionAttempt,
+    /// SYN with ACK: the reply to a connection attempt.
+    Reply,
+    /// FIN or RST: the connection is being torn down.
+    Teardown,
+    /// ACK without SYN/FIN/RST: data or keepalive on an established connection.
+    Established,
+    /// Any other combination.
+    Unknown,
+}
+
+///An inconsistency between the flags and the other fields of a TCP segment, see [`TcpInfo::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpValidationError {
+    /// The ACK flag is set but no acknowledgment number is logged.
+    MissingAckNumber,
+    /// The URG flag is set but no urgent pointer is logged.
+    MissingUrgentPointer,
+    /// SYN and FIN are both set, never sent by a legitimate stack.
+    SynFin,
+    /// SYN and RST are both set, never sent by a legitimate stack.
+    SynRst,
+}
+
+impl Display for TcpValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TcpValidationError::MissingAckNumber => write!(f, "ACK set without ack number"),
+            TcpValidationError::MissingUrgentPointer => write!(f, "URG set without urgent pointer"),
+            TcpValidationError::SynFin => write!(f, "SYN and FIN both set"),
+            TcpValidationError::SynRst => write!(f, "SYN and RST both set"),
+        }
+    }
+}
+impl Error for TcpValidationError {}
+
+///The sequence, acknowledgment and window fields of a TCP segment, grouped for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TcpTiming {
+    pub sequence_number: String,
+    pub ack_number: Option<u32>,
+    pub window: u32,
+}
+
+impl Display for TcpTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "seq {}", self.sequence_number)?;
+        if let Some(ack_number) = self.ack_number {
+            write!(f, ", ack {}", ack_number)?;
+        }
+        write!(f, ", win {}", self.window)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TcpInfo {
@@ -68,21 +390,86 @@ pub struct TcpInfo {
     pub ack_number: Option<u32>,
     pub window: u32,
     pub urg: Option<u32>,
-    pub options: String,
+    pub options: TcpOptions,
+}
+
+impl TcpInfo {
+    /// Decodes the logged flags letters.
+    pub fn tcp_flags(&self) -> TcpFlags {
+        //Infallible, unknown letters are ignored
+        TcpFlags::from_str(&self.flags).unwrap()
+    }
+
+    /// Checks that the flags agree with the other fields, returning the first inconsistency found.
+    pub fn validate(&self) -> Result<(), TcpValidationError> {
+        let flags = self.tcp_flags();
+        if flags.ack && self.ack_number.is_none() {
+            return Err(TcpValidationError::MissingAckNumber);
+        }
+        if flags.urg && self.urg.is_none() {
+            return Err(TcpValidationError::MissingUrgentPointer);
+        }
+        if flags.syn && flags.fin {
+            return Err(TcpValidationError::SynFin);
+        }
+        if flags.syn && flags.rst {
+            return Err(TcpValidationError::SynRst);
+        }
+        Ok(())
+    }
+
+    /// Returns the sequence number, acknowledgment number and window together.
+    pub fn timing(&self) -> TcpTiming {
+        TcpTiming {
+            sequence_number: self.sequence_number.clone(),
+            ack_number: self.ack_number,
+            window: self.window,
+        }
+    }
+
+    /// Infers a coarse connection state from the flags.
+    pub fn state_hint(&self) -> TcpStateHint {
+        let flags = self.tcp_flags();
+        if flags.fin || flags.rst {
+            TcpStateHint::Teardown
+        } else if flags.syn && flags.ack {
+            TcpStateHint::Reply
+        } else if flags.syn {
+            TcpStateHint::ConnectionAttempt
+        } else if flags.ack {
+            TcpStateHint::Established
+        } else {
+            TcpStateHint::Unknown
+        }
+    }
 }
 
-pub(crate) fn parse_tcp_info(input: &str) -> IResult<&str, ProtoInfo> {
+pub(crate) fn parse_tcp_info<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, ProtoInfo> {
     let (next, ports) = parse_src_dst_ports(input)?;
-    let (next, data_len) = csv(parse_u32)(next)?;
+    //Pure ACK segments are sometimes logged with a blank data length
+    let (next, data_len) = csv(opt(parse_u32))(next)?;
+    let data_len = data_len.unwrap_or(0);
     let (next, flags) = csv(parse_utf8_string)(next)?;
 
     //Todo use a struct to rapresent range
     let (next, sequence_number) = csv(take_till(|c| c == ',')).map(|s| s.into()).parse(next)?;
 
-    let (next, ack_number) = csv(opt(parse_u32))(next)?;
+    let (next, ack_number) = csv(opt_field(parse_u32))(next)?;
     let (next, window) = csv(parse_u32)(next)?;
-    let (next, urg) = csv(opt(parse_u32))(next)?;
-    let (next, options) = rest(next).map(|f| (f.0, f.1.into()))?;
+    let (next, urg) = csv(opt_field(parse_u32))(next)?;
+    let (ack_number, urg) = match options.swapped_urg_ack {
+        true => (urg, ack_number),
+        false => (ack_number, urg),
+    };
+    //Exact parsing leaves any extra column unconsumed instead of folding it into the options
+    let (next, tcp_options) = if options.exact {
+        map_res(take_till(|c| c == ','), TcpOptions::from_str)(next)?
+    } else {
+        map_res(rest, TcpOptions::from_str)(next)?
+    };
 
     let tcp_info = TcpInfo {
         ports,
@@ -92,7 +479,7 @@ pub(crate) fn parse_tcp_info(input: &str) -> IResult<&str, ProtoInfo> {
         ack_number,
         window,
         urg,
-        options,
+        options: tcp_options,
     };
 
     Ok((next, ProtoInfo::TcpInfo(tcp_info)))
@@ -107,11 +494,224 @@ pub struct UdpInfo {
 
 pub(crate) fn parse_udp_info(input: &str) -> IResult<&str, ProtoInfo> {
     let (next, ports) = parse_src_dst_ports(input)?;
-    let (next, data_len) = terminated(parse_u32, eof)(next)?;
+    //Tolerate trailing whitespace such as an untrimmed newline
+    let (next, data_len) = terminated(parse_u32, terminated(multispace0, eof))(next)?;
 
     Ok((next, ProtoInfo::UdpInfo(UdpInfo { ports, data_len })))
 }
 
+///The packet that triggered an ICMP error, as logged by pf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EmbeddedPacket {
+    /// Destination address of the original packet.
+    pub dst: IpAddr,
+    /// Protocol number of the original packet, logged for `unreachproto` and `unreachport`.
+    pub proto_num: Option<u8>,
+    /// Destination port of the original packet, logged for `unreachport`.
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IcmpInfo {
+    /// The ICMP type keyword (e.g. `request`, `reply`, `unreachport`).
+    ///
+    /// When the type is logged numerically it's decoded to its keyword,
+    /// or kept as the number if there is none.
+    pub icmp_type: String,
+    /// The numeric ICMP type, logged or decoded from the keyword.
+    pub type_num: Option<u8>,
+    /// The ICMP code, only available when the type is logged numerically.
+    pub code: Option<u8>,
+    /// Identifier of echo and timestamp messages.
+    pub id: Option<u16>,
+    /// Sequence number of echo and timestamp messages.
+    pub seq: Option<u16>,
+    /// The original packet of an unreachable or need-fragmentation error.
+    pub embedded: Option<EmbeddedPacket>,
+    /// The next-hop MTU of a need-fragmentation error.
+    pub mtu: Option<u32>,
+    /// Remaining type specific fields, kept raw (e.g. descriptions or timestamps).
+    pub details: Option<String>,
+}
+
+fn comma<'a, F, O>(parser: F) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    preceded(char(','), parser)
+}
+
+///ICMP types and the keywords used to log them.
+const ICMP_TYPES: [(u8, &str); 11] = [
+    (0, "reply"),
+    (3, "unreach"),
+    (3, "unreachproto"),
+    (3, "unreachport"),
+    (3, "needfrag"),
+    (5, "redirect"),
+    (8, "request"),
+    (11, "timexceed"),
+    (12, "paramprob"),
+    (13, "tstamp"),
+    (14, "tstampreply"),
+];
+
+///Parses a numerically logged type and code (e.g. `8,0`).
+fn parse_icmp_type_code(input: &str) -> IResult<&str, (u8, u8)> {
+    pair(parse_u8, comma(parse_u8))(input)
+}
+
+pub(crate) fn parse_icmp_info(input: &str) -> IResult<&str, ProtoInfo> {
+    //Keywords never start with a digit, so a numeric type and code can't be mistaken for one
+    if let Ok((next, (type_num, code))) = parse_icmp_type_code(input) {
+        let icmp_type = ICMP_TYPES
+            .iter()
+            .find(|(num, _)| *num == type_num)
+            .map(|(_, keyword)| keyword.to_string())
+            .unwrap_or_else(|| type_num.to_string());
+
+        let (next, details) = opt(comma(rest))(next)?;
+        let (next, _) = terminated(multispace0, eof)(next)?;
+
+        let icmp_info = IcmpInfo {
+            icmp_type,
+            type_num: Some(type_num),
+            code: Some(code),
+            id: None,
+            seq: None,
+            embedded: None,
+            mtu: None,
+            details: details.map(|details: &str| details.trim_end().into()),
+        };
+        return Ok((next, ProtoInfo::IcmpInfo(icmp_info)));
+    }
+
+    let (mut next, icmp_type) = parse_utf8_string(input)?;
+    let type_num = ICMP_TYPES
+        .iter()
+        .find(|(_, keyword)| *keyword == icmp_type)
+        .map(|(num, _)| *num);
+
+    let mut icmp_info = IcmpInfo {
+        icmp_type,
+        type_num,
+        code: None,
+        id: None,
+        seq: None,
+        embedded: None,
+        mtu: None,
+        details: None,
+    };
+
+    match icmp_info.icmp_type.as_str() {
+        "request" | "reply" | "tstamp" | "tstampreply" => {
+            let (after, (id, seq)) = pair(comma(parse_u16), comma(parse_u16))(next)?;
+            icmp_info.id = Some(id);
+            icmp_info.seq = Some(seq);
+            next = after;
+        }
+        "unreachproto" => {
+            let (after, (dst, proto_num)) = pair(comma(parse_ip_addr), comma(parse_u8))(next)?;
+            icmp_info.embedded = Some(EmbeddedPacket {
+                dst,
+                proto_num: Some(proto_num),
+                port: None,
+            });
+            next = after;
+        }
+        "unreachport" => {
+            let (after, (dst, proto_num, port)) =
+                tuple((comma(parse_ip_addr), comma(parse_u8), comma(parse_u16)))(next)?;
+            icmp_info.embedded = Some(EmbeddedPacket {
+                dst,
+                proto_num: Some(proto_num),
+                port: Some(port),
+            });
+            next = after;
+        }
+        "needfrag" => {
+            let (after, (dst, mtu)) = pair(comma(parse_ip_addr), comma(parse_u32))(next)?;
+            icmp_info.embedded = Some(EmbeddedPacket {
+                dst,
+                proto_num: None,
+                port: None,
+            });
+            icmp_info.mtu = Some(mtu);
+            next = after;
+        }
+        _ => {}
+    }
+
+    let (next, details) = opt(comma(rest))(next)?;
+    let (next, _) = terminated(multispace0, eof)(next)?;
+    icmp_info.details = details.map(|details: &str| details.trim_end().into());
+
+    Ok((next, ProtoInfo::IcmpInfo(icmp_info)))
+}
+
+///VRRP advertisement fields, logged as `version,vrid,priority` when available.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VrrpInfo {
+    pub version: Option<u8>,
+    pub vrid: Option<u8>,
+    pub priority: Option<u8>,
+}
+
+pub(crate) fn parse_vrrp_info(input: &str) -> IResult<&str, ProtoInfo> {
+    let (next, fields) = opt(tuple((csv(parse_u8), csv(parse_u8), parse_u8)))(input)?;
+    let (next, _) = terminated(multispace0, eof)(next)?;
+
+    let vrrp_info = match fields {
+        Some((version, vrid, priority)) => VrrpInfo {
+            version: Some(version),
+            vrid: Some(vrid),
+            priority: Some(priority),
+        },
+        None => VrrpInfo::default(),
+    };
+
+    Ok((next, ProtoInfo::VrrpInfo(vrrp_info)))
+}
+
+///PIM message fields, the message type when it is logged.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PimInfo {
+    pub pim_type: Option<String>,
+}
+
+pub(crate) fn parse_pim_info(input: &str) -> IResult<&str, ProtoInfo> {
+    let (next, pim_type) = parse_utf8_string(input)?;
+    let (next, _) = terminated(multispace0, eof)(next)?;
+
+    let pim_type = Some(pim_type.trim_end().to_string()).filter(|pim_type| !pim_type.is_empty());
+    Ok((next, ProtoInfo::PimInfo(PimInfo { pim_type })))
+}
+
+///DCCP fields: the ports, followed by the service code when it is logged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DccpInfo {
+    pub ports: Ports,
+    pub service_code: Option<u32>,
+}
+
+pub(crate) fn parse_dccp_info(input: &str) -> IResult<&str, ProtoInfo> {
+    let (next, ports) = parse_src_dst_ports(input)?;
+    let (next, service_code) = terminated(opt(parse_u32), terminated(multispace0, eof))(next)?;
+
+    Ok((
+        next,
+        ProtoInfo::DccpInfo(DccpInfo {
+            ports,
+            service_code,
+        }),
+    ))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CarpInfo {}
@@ -121,17 +721,117 @@ pub struct CarpInfo {}
 pub enum ProtoInfo {
     UdpInfo(UdpInfo),
     TcpInfo(TcpInfo),
+    IcmpInfo(IcmpInfo),
+    VrrpInfo(VrrpInfo),
+    PimInfo(PimInfo),
+    DccpInfo(DccpInfo),
     //TODO CarpInfo(CarpInfo),
     UnknownInfo(String),
 }
 
+impl ProtoInfo {
+    /// Returns the source and destination ports for port-bearing protocols.
+    pub fn ports(&self) -> Option<&Ports> {
+        match self {
+            ProtoInfo::UdpInfo(udp_info) => Some(&udp_info.ports),
+            ProtoInfo::TcpInfo(tcp_info) => Some(&tcp_info.ports),
+            ProtoInfo::DccpInfo(dccp_info) => Some(&dccp_info.ports),
+            ProtoInfo::IcmpInfo(_)
+            | ProtoInfo::VrrpInfo(_)
+            | ProtoInfo::PimInfo(_)
+            | ProtoInfo::UnknownInfo(_) => None,
+        }
+    }
+
+    /// Returns `false` only for [`ProtoInfo::UnknownInfo`], when the payload was kept raw
+    /// instead of being parsed into a structure.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, ProtoInfo::UnknownInfo(_))
+    }
+
+    /// Formats the protocol-specific columns as they appear at the end of the log,
+    /// e.g. `49678,161,86` for UDP.
+    ///
+    /// Together with [`crate::ip::IpSpecific::to_log_fields`] it rebuilds the tail of the line.
+    pub fn to_log_fields(&self) -> String {
+        let ports = |ports: &Ports| vec![ports.srcport.to_string(), ports.dstport.to_string()];
+        let opt = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+
+        let fields = match self {
+            ProtoInfo::TcpInfo(tcp_info) => {
+                let mut fields = ports(&tcp_info.ports);
+                fields.extend([
+                    tcp_info.data_len.to_string(),
+                    tcp_info.flags.clone(),
+                    tcp_info.sequence_number.clone(),
+                    opt(tcp_info.ack_number),
+                    tcp_info.window.to_string(),
+                    opt(tcp_info.urg),
+                    tcp_info.options.to_string(),
+                ]);
+                fields
+            }
+            ProtoInfo::UdpInfo(udp_info) => {
+                let mut fields = ports(&udp_info.ports);
+                fields.push(udp_info.data_len.to_string());
+                fields
+            }
+            ProtoInfo::IcmpInfo(icmp_info) => {
+                //A logged code means the type was logged numerically
+                let mut fields = match (icmp_info.type_num, icmp_info.code) {
+                    (Some(type_num), Some(code)) => vec![type_num.to_string(), code.to_string()],
+                    _ => vec![icmp_info.icmp_type.clone()],
+                };
+                fields.extend(icmp_info.id.map(|id| id.to_string()));
+                fields.extend(icmp_info.seq.map(|seq| seq.to_string()));
+                if let Some(embedded) = &icmp_info.embedded {
+                    fields.push(embedded.dst.to_string());
+                    fields.extend(embedded.proto_num.map(|num| num.to_string()));
+                    fields.extend(embedded.port.map(|port| port.to_string()));
+                }
+                fields.extend(icmp_info.mtu.map(|mtu| mtu.to_string()));
+                fields.extend(icmp_info.details.clone());
+                fields
+            }
+            ProtoInfo::VrrpInfo(vrrp_info) => {
+                match (vrrp_info.version, vrrp_info.vrid, vrrp_info.priority) {
+                    (Some(version), Some(vrid), Some(priority)) => {
+                        vec![version.to_string(), vrid.to_string(), priority.to_string()]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            ProtoInfo::PimInfo(pim_info) => pim_info.pim_type.clone().into_iter().collect(),
+            ProtoInfo::DccpInfo(dccp_info) => {
+                let mut fields = ports(&dccp_info.ports);
+                fields.extend(dccp_info.service_code.map(|code| code.to_string()));
+                fields
+            }
+            ProtoInfo::UnknownInfo(raw) => vec![raw.clone()],
+        };
+
+        fields.join(",")
+    }
+}
+
 pub(crate) fn parse_proto_info<'a>(
     input: &'a str,
     proto: &ProtoName,
+    options: &ParseOptions,
 ) -> IResult<&'a str, ProtoInfo> {
+    if !options.is_structured(proto) {
+        return rest
+            .map(|raw: &str| ProtoInfo::UnknownInfo(raw.into()))
+            .parse(input);
+    }
+
     let (next, proto_info) = match proto {
-        ProtoName::Tcp => parse_tcp_info(input)?,
+        ProtoName::Tcp => parse_tcp_info(input, options)?,
         ProtoName::Udp => parse_udp_info(input)?,
+        ProtoName::Icmp => parse_icmp_info(input)?,
+        ProtoName::Vrrp => parse_vrrp_info(input)?,
+        ProtoName::Pim => parse_pim_info(input)?,
+        ProtoName::Dccp => parse_dccp_info(input)?,
         ProtoName::Other(_) => terminated(parse_utf8_string, eof)
             .map(ProtoInfo::UnknownInfo)
             .parse(input)?,
@@ -141,4 +841,575 @@ pub(crate) fn parse_proto_info<'a>(
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    #[test]
+    fn protocol_from_name() {
+        assert_eq!(
+            Protocol {
+                num: 6,
+                name: ProtoName::Tcp
+            },
+            Protocol::tcp()
+        );
+        assert_eq!(
+            Protocol {
+                num: 17,
+                name: ProtoName::Udp
+            },
+            Protocol::udp()
+        );
+        assert_eq!(
+            Protocol {
+                num: 1,
+                name: ProtoName::Icmp
+            },
+            Protocol::from_name(ProtoName::Icmp)
+        );
+        assert_eq!(
+            RESERVED_PROTO_NUM,
+            Protocol::from_name(ProtoName::Other("unknown".into())).num
+        );
+    }
+
+    #[test]
+    fn proto_name_from_num() {
+        assert_eq!(ProtoName::Tcp, ProtoName::from_num(6));
+        assert_eq!(ProtoName::Udp, ProtoName::from_num(17));
+        assert_eq!(ProtoName::Other("gre".into()), ProtoName::from_num(47));
+        assert_eq!(ProtoName::Other("250".into()), ProtoName::from_num(250));
+    }
+
+    #[test]
+    fn proto_name_keeps_casing() {
+        //Known names are matched case-sensitively, anything else keeps its raw form
+        assert_eq!(
+            ProtoName::Other("TCP".into()),
+            ProtoName::from_str("TCP").unwrap()
+        );
+        assert_eq!("GRE", ProtoName::from_str("GRE").unwrap().to_string());
+    }
+
+    #[test]
+    fn parse_pim() {
+        assert_eq!(ProtoName::Pim, ProtoName::from_num(103));
+        assert_eq!(Some(103), ProtoName::Pim.num());
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::PimInfo(PimInfo {
+                    pim_type: Some("hello".into())
+                })
+            )),
+            parse_pim_info("hello")
+        );
+        assert_eq!(
+            Ok(("", ProtoInfo::PimInfo(PimInfo::default()))),
+            parse_pim_info("\n")
+        );
+        assert!(parse_pim_info("hello,extra").is_err());
+    }
+
+    #[test]
+    fn parse_dccp() {
+        assert_eq!(ProtoName::Dccp, ProtoName::from_num(33));
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::DccpInfo(DccpInfo {
+                    ports: Ports {
+                        srcport: 5004,
+                        dstport: 5005
+                    },
+                    service_code: Some(1),
+                })
+            )),
+            parse_dccp_info("5004,5005,1")
+        );
+
+        let (_, proto_info) = parse_dccp_info("5004,5005,\n").unwrap();
+        assert!(matches!(
+            proto_info,
+            ProtoInfo::DccpInfo(DccpInfo {
+                service_code: None,
+                ..
+            })
+        ));
+        assert!(parse_dccp_info("5004,5005,1,extra").is_err());
+    }
+
+    #[test]
+    fn parse_icmp_echo() {
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::IcmpInfo(IcmpInfo {
+                    icmp_type: "request".into(),
+                    type_num: Some(8),
+                    code: None,
+                    id: Some(29474),
+                    seq: Some(1),
+                    embedded: None,
+                    mtu: None,
+                    details: None,
+                })
+            )),
+            parse_icmp_info("request,29474,1")
+        );
+    }
+
+    #[test]
+    fn parse_icmp_unreachable() {
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::IcmpInfo(IcmpInfo {
+                    icmp_type: "unreachport".into(),
+                    type_num: Some(3),
+                    code: None,
+                    id: None,
+                    seq: None,
+                    embedded: Some(EmbeddedPacket {
+                        dst: "10.0.0.1".parse().unwrap(),
+                        proto_num: Some(17),
+                        port: Some(53),
+                    }),
+                    mtu: None,
+                    details: None,
+                })
+            )),
+            parse_icmp_info("unreachport,10.0.0.1,17,53")
+        );
+
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::IcmpInfo(IcmpInfo {
+                    icmp_type: "unreachproto".into(),
+                    type_num: Some(3),
+                    code: None,
+                    id: None,
+                    seq: None,
+                    embedded: Some(EmbeddedPacket {
+                        dst: "2001:db8::1".parse().unwrap(),
+                        proto_num: Some(132),
+                        port: None,
+                    }),
+                    mtu: None,
+                    details: None,
+                })
+            )),
+            parse_icmp_info("unreachproto,2001:db8::1,132")
+        );
+
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::IcmpInfo(IcmpInfo {
+                    icmp_type: "needfrag".into(),
+                    type_num: Some(3),
+                    code: None,
+                    id: None,
+                    seq: None,
+                    embedded: Some(EmbeddedPacket {
+                        dst: "10.0.0.1".parse().unwrap(),
+                        proto_num: None,
+                        port: None,
+                    }),
+                    mtu: Some(1400),
+                    details: None,
+                })
+            )),
+            parse_icmp_info("needfrag,10.0.0.1,1400")
+        );
+
+        assert!(parse_icmp_info("unreachport,10.0.0.a,17,53").is_err());
+    }
+
+    #[test]
+    fn parse_icmp_numeric() {
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::IcmpInfo(IcmpInfo {
+                    icmp_type: "request".into(),
+                    type_num: Some(8),
+                    code: Some(0),
+                    id: None,
+                    seq: None,
+                    embedded: None,
+                    mtu: None,
+                    details: None,
+                })
+            )),
+            parse_icmp_info("8,0")
+        );
+
+        let (_, proto_info) = parse_icmp_info("42,1,extra").unwrap();
+        assert!(matches!(
+            proto_info,
+            ProtoInfo::IcmpInfo(IcmpInfo { icmp_type, type_num: Some(42), code: Some(1), details: Some(details), .. })
+                if icmp_type == "42" && details == "extra"
+        ));
+    }
+
+    #[test]
+    fn parse_icmp_details() {
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::IcmpInfo(IcmpInfo {
+                    icmp_type: "unreach".into(),
+                    type_num: Some(3),
+                    code: None,
+                    id: None,
+                    seq: None,
+                    embedded: None,
+                    mtu: None,
+                    details: Some("host unreachable".into()),
+                })
+            )),
+            parse_icmp_info("unreach,host unreachable\n")
+        );
+        assert!(parse_icmp_info("request,1,1 garbage").is_err());
+    }
+
+    #[test]
+    fn port_out_of_range() {
+        assert!(has_port_out_of_range("70000,161,86"));
+        assert!(has_port_out_of_range("49678,99999999999999999999,86"));
+        assert!(!has_port_out_of_range("49678,161,70000"));
+        assert!(!has_port_out_of_range("49678,abc,86"));
+        assert!(!has_port_out_of_range(""));
+    }
+
+    #[test]
+    fn ports_dst_service() {
+        let ports = |dstport| Ports {
+            srcport: 49678,
+            dstport,
+        };
+        assert_eq!(Some("dns"), ports(53).dst_service());
+        assert_eq!(Some("https"), ports(443).dst_service());
+        assert_eq!(Some("snmp"), ports(161).dst_service());
+        assert_eq!(None, ports(49152).dst_service());
+    }
+
+    #[test]
+    fn parse_tcp_info_blank_data_len() {
+        let (_, proto_info) = parse_tcp_info(
+            "443,52461,,A,,3442468762,501,,nop;nop;TS",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert!(matches!(proto_info, ProtoInfo::TcpInfo(tcp_info) if tcp_info.data_len == 0));
+    }
+
+    #[test]
+    fn tcp_validate() {
+        let tcp_info = |flags: &str, ack: &str, urg: &str| {
+            let input = format!(
+                "52461,9100,0,{},3442468761,{},64240,{},mss",
+                flags, ack, urg
+            );
+            match parse_tcp_info(&input, &ParseOptions::default()) {
+                Ok((_, ProtoInfo::TcpInfo(tcp_info))) => tcp_info,
+                other => panic!("expected TCP info, got {:?}", other),
+            }
+        };
+
+        assert_eq!(Ok(()), tcp_info("S", "", "").validate());
+        assert_eq!(Ok(()), tcp_info("SA", "1", "").validate());
+        assert_eq!(Ok(()), tcp_info("PAU", "1", "5").validate());
+        assert_eq!(
+            Err(TcpValidationError::MissingAckNumber),
+            tcp_info("A", "", "").validate()
+        );
+        assert_eq!(
+            Err(TcpValidationError::MissingUrgentPointer),
+            tcp_info("U", "", "").validate()
+        );
+        assert_eq!(
+            Err(TcpValidationError::SynFin),
+            tcp_info("SF", "", "").validate()
+        );
+        assert_eq!(
+            Err(TcpValidationError::SynRst),
+            tcp_info("SR", "", "").validate()
+        );
+    }
+
+    #[test]
+    fn tcp_timing() {
+        let (_, proto_info) = parse_tcp_info(
+            "443,52461,0,A,1:25,3442468762,501,,nop;nop;TS",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let ProtoInfo::TcpInfo(tcp_info) = proto_info else {
+            panic!("expected TCP info");
+        };
+
+        let timing = tcp_info.timing();
+        assert_eq!(
+            TcpTiming {
+                sequence_number: "1:25".into(),
+                ack_number: Some(3442468762),
+                window: 501,
+            },
+            timing
+        );
+        assert_eq!("seq 1:25, ack 3442468762, win 501", timing.to_string());
+
+        let syn = TcpTiming {
+            ack_number: None,
+            ..timing
+        };
+        assert_eq!("seq 1:25, win 501", syn.to_string());
+    }
+
+    #[test]
+    fn parse_tcp_info_exact() {
+        let input = "52461,9100,0,S,3442468761,,64240,,mss;nop,extra";
+        let exact = ParseOptions {
+            exact: true,
+            ..Default::default()
+        };
+
+        let (next, proto_info) = parse_tcp_info(input, &exact).unwrap();
+        assert_eq!(",extra", next);
+        assert!(
+            matches!(proto_info, ProtoInfo::TcpInfo(tcp_info) if tcp_info.options.to_string() == "mss;nop")
+        );
+
+        let (next, proto_info) = parse_tcp_info(input, &ParseOptions::default()).unwrap();
+        assert_eq!("", next);
+        assert!(
+            matches!(proto_info, ProtoInfo::TcpInfo(tcp_info) if tcp_info.options.to_string() == "mss;nop,extra")
+        );
+    }
+
+    #[test]
+    fn parse_vrrp() {
+        assert_eq!(
+            Ok((
+                "",
+                ProtoInfo::VrrpInfo(VrrpInfo {
+                    version: Some(2),
+                    vrid: Some(10),
+                    priority: Some(100),
+                })
+            )),
+            parse_vrrp_info("2,10,100")
+        );
+        assert_eq!(
+            Ok(("", ProtoInfo::VrrpInfo(VrrpInfo::default()))),
+            parse_vrrp_info("")
+        );
+        assert!(parse_vrrp_info("2,10").is_err());
+        assert_eq!(Some(112), ProtoName::Vrrp.num());
+        assert_eq!(Ok(ProtoName::Vrrp), ProtoName::from_str("vrrp"));
+    }
+
+    #[test]
+    fn tcp_flags_from_str() {
+        assert_eq!(
+            TcpFlags {
+                syn: true,
+                ack: true,
+                ..Default::default()
+            },
+            TcpFlags::from_str("SA").unwrap()
+        );
+        assert_eq!(
+            TcpFlags {
+                fin: true,
+                psh: true,
+                ack: true,
+                urg: true,
+                ece: true,
+                cwr: true,
+                ..Default::default()
+            },
+            TcpFlags::from_str("FPAUEW").unwrap()
+        );
+        assert_eq!(TcpFlags::default(), TcpFlags::from_str("").unwrap());
+    }
+
+    #[test]
+    fn tcp_state_hint() {
+        let (_, proto_info) = parse_tcp_info(
+            "52461,9100,0,S,3442468761,,64240,,mss",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let ProtoInfo::TcpInfo(mut tcp_info) = proto_info else {
+            panic!("expected TcpInfo");
+        };
+        assert_eq!(TcpStateHint::ConnectionAttempt, tcp_info.state_hint());
+
+        for (flags, hint) in [
+            ("SA", TcpStateHint::Reply),
+            ("FA", TcpStateHint::Teardown),
+            ("R", TcpStateHint::Teardown),
+            ("RA", TcpStateHint::Teardown),
+            ("A", TcpStateHint::Established),
+            ("PA", TcpStateHint::Established),
+            ("", TcpStateHint::Unknown),
+        ] {
+            tcp_info.flags = flags.into();
+            assert_eq!(hint, tcp_info.state_hint(), "flags {}", flags);
+        }
+    }
+
+    #[test]
+    fn proto_name_from_numeric_str() {
+        assert_eq!(Ok(ProtoName::Tcp), ProtoName::from_str("6"));
+        assert_eq!(
+            Ok(ProtoName::Other("esp".into())),
+            ProtoName::from_str("50")
+        );
+        assert_eq!(
+            Ok(ProtoName::Other("250".into())),
+            ProtoName::from_str("250")
+        );
+        assert_eq!(
+            Ok(ProtoName::Other("300".into())),
+            ProtoName::from_str("300")
+        );
+        assert_eq!(Ok(ProtoName::Other("".into())), ProtoName::from_str(""));
+        assert_eq!(Ok(ProtoName::Vrrp), ProtoName::from_str("112"));
+        assert_eq!(Some(112), ProtoName::Other("carp".into()).num());
+    }
+
+    #[test]
+    fn tcp_options_round_trip() {
+        let raw = "mss;nop;wscale;nop;nop;sackOK";
+        let options = TcpOptions::from_str(raw).unwrap();
+        assert_eq!(
+            TcpOptions(vec![
+                TcpOption::Mss,
+                TcpOption::Nop,
+                TcpOption::Wscale,
+                TcpOption::Nop,
+                TcpOption::Nop,
+                TcpOption::SackOk,
+            ]),
+            options
+        );
+        assert_eq!(raw, options.to_string());
+
+        let unknown = TcpOptions::from_str("TS;unknown-30").unwrap();
+        assert_eq!(
+            TcpOptions(vec![
+                TcpOption::Timestamp,
+                TcpOption::Other("unknown-30".into())
+            ]),
+            unknown
+        );
+        assert_eq!("TS;unknown-30", unknown.to_string());
+
+        let empty = TcpOptions::from_str("").unwrap();
+        assert_eq!(TcpOptions::default(), empty);
+        assert_eq!("", empty.to_string());
+    }
+
+    #[test]
+    fn parse_udp_info_trailing_whitespace() {
+        let expected = ProtoInfo::UdpInfo(UdpInfo {
+            ports: Ports {
+                srcport: 49678,
+                dstport: 161,
+            },
+            data_len: 86,
+        });
+
+        assert_eq!(Ok(("", expected.clone())), parse_udp_info("49678,161,86"));
+        assert_eq!(Ok(("", expected.clone())), parse_udp_info("49678,161,86\n"));
+        assert_eq!(Ok(("", expected)), parse_udp_info("49678,161,86 \r\n"));
+        assert!(parse_udp_info("49678,161,86,extra").is_err());
+        assert!(parse_udp_info("49678,161,86\nextra").is_err());
+    }
+
+    #[test]
+    fn parse_proto_info_structured_protocols() {
+        let tcp = "52461,9100,0,S,3442468761,,64240,,mss";
+        let udp = "49678,161,86";
+        let options = ParseOptions {
+            structured_protocols: Some(vec![ProtoName::Udp]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Ok(("", ProtoInfo::UnknownInfo(tcp.into()))),
+            parse_proto_info(tcp, &ProtoName::Tcp, &options)
+        );
+        assert!(matches!(
+            parse_proto_info(udp, &ProtoName::Udp, &options),
+            Ok(("", ProtoInfo::UdpInfo(_)))
+        ));
+        assert!(matches!(
+            parse_proto_info(tcp, &ProtoName::Tcp, &ParseOptions::default()),
+            Ok(("", ProtoInfo::TcpInfo(_)))
+        ));
+    }
+
+    #[test]
+    fn parse_tcp_info_swapped_urg_ack() {
+        let options = ParseOptions {
+            swapped_urg_ack: true,
+            ..Default::default()
+        };
+        let (_, proto_info) = parse_tcp_info("443,52461,0,AU,100,7,501,1234,", &options).unwrap();
+        let ProtoInfo::TcpInfo(tcp_info) = proto_info else {
+            panic!("expected TcpInfo");
+        };
+        assert_eq!(Some(1234), tcp_info.ack_number);
+        assert_eq!(Some(7), tcp_info.urg);
+        assert_eq!(501, tcp_info.window);
+
+        let (_, proto_info) =
+            parse_tcp_info("443,52461,0,AU,100,7,501,1234,", &ParseOptions::default()).unwrap();
+        let ProtoInfo::TcpInfo(tcp_info) = proto_info else {
+            panic!("expected TcpInfo");
+        };
+        assert_eq!(Some(7), tcp_info.ack_number);
+        assert_eq!(Some(1234), tcp_info.urg);
+    }
+
+    #[test]
+    fn is_known_test() {
+        let options = ParseOptions::default();
+        let (_, udp) = parse_proto_info("49678,161,86", &ProtoName::Udp, &options).unwrap();
+        assert!(udp.is_known());
+
+        let gre = ProtoName::Other("gre".into());
+        let (_, unknown) = parse_proto_info("raw payload", &gre, &options).unwrap();
+        assert!(!unknown.is_known());
+    }
+
+    #[test]
+    fn to_log_fields_round_trip() {
+        let options = ParseOptions::default();
+        let tails = [
+            (
+                ProtoName::Tcp,
+                "52461,9100,0,S,3442468761,,64240,,mss;nop;wscale;nop;nop;sackOK",
+            ),
+            (ProtoName::Tcp, "443,52461,0,A,,1234,501,,"),
+            (ProtoName::Udp, "49678,161,86"),
+            (ProtoName::Icmp, "request,1234,1"),
+            (ProtoName::Icmp, "unreachport,10.0.0.1,17,53"),
+            (ProtoName::Icmp, "3,4,extra"),
+            (ProtoName::Vrrp, "2,20,100"),
+            (ProtoName::Dccp, "5001,5002,42"),
+            (ProtoName::Other("gre".into()), "raw payload"),
+        ];
+
+        for (proto, tail) in tails {
+            let (_, proto_info) = parse_proto_info(tail, &proto, &options).unwrap();
+            assert_eq!(tail, proto_info.to_log_fields());
+        }
+    }
+}
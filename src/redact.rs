@@ -0,0 +1,189 @@
+use std::fmt::{Debug, Display};
+use std::net::IpAddr;
+
+use crate::ip::IpData;
+use crate::log::FwLog;
+use crate::protocol::{EmbeddedPacket, IcmpInfo, ProtoInfo};
+
+///A wrapper that masks the source and destination addresses when formatted.
+///
+/// `Redacted<&FwLog>` keeps the first half of each address (`192.168.x.x`,
+/// `2001:db8:x:x:x:x:x:x`) so logs can still be bucketed by network without
+/// leaking the exact hosts. The derived `Debug` on `FwLog` is left untouched.
+///
+/// # Example
+/// ```rust
+/// use senpa::{parse_log, Redacted};
+///
+/// let log = parse_log("96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+/// 4,0x0,,127,58940,0,none,17,udp,\
+/// 106,192.168.10.15,192.168.20.11,49678,161,86").unwrap();
+///
+/// let printed = format!("{:?}", Redacted(&log));
+/// assert!(printed.contains("192.168.x.x"));
+/// assert!(!printed.contains("192.168.10.15"));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Redacted<T>(pub T);
+
+fn mask_addr(addr: &IpAddr) -> String {
+    match addr {
+        IpAddr::V4(v4) => {
+            let octets = v4.octets();
+            format!("{}.{}.x.x", octets[0], octets[1])
+        }
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            format!("{:x}:{:x}:x:x:x:x:x:x", segments[0], segments[1])
+        }
+    }
+}
+
+struct MaskedAddr<'a>(&'a IpAddr);
+
+impl Debug for MaskedAddr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&mask_addr(self.0))
+    }
+}
+
+impl Debug for Redacted<&IpData> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IpData")
+            .field("length", &self.0.length)
+            .field("src", &MaskedAddr(&self.0.src))
+            .field("dst", &MaskedAddr(&self.0.dst))
+            .finish()
+    }
+}
+
+impl Debug for Redacted<&EmbeddedPacket> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddedPacket")
+            .field("dst", &MaskedAddr(&self.0.dst))
+            .field("proto_num", &self.0.proto_num)
+            .field("port", &self.0.port)
+            .finish()
+    }
+}
+
+impl Debug for Redacted<&IcmpInfo> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IcmpInfo")
+            .field("icmp_type", &self.0.icmp_type)
+            .field("type_num", &self.0.type_num)
+            .field("code", &self.0.code)
+            .field("id", &self.0.id)
+            .field("seq", &self.0.seq)
+            .field("embedded", &self.0.embedded.as_ref().map(Redacted))
+            .field("mtu", &self.0.mtu)
+            .field("details", &self.0.details)
+            .finish()
+    }
+}
+
+impl Debug for Redacted<&ProtoInfo> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            ProtoInfo::IcmpInfo(icmp_info) => f
+                .debug_tuple("IcmpInfo")
+                .field(&Redacted(icmp_info))
+                .finish(),
+            other => Debug::fmt(other, f),
+        }
+    }
+}
+
+impl Debug for Redacted<&FwLog> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FwLog")
+            .field("packet_filter", &self.0.packet_filter)
+            .field("ip_specific", &self.0.ip_specific)
+            .field("ip_data", &Redacted(&self.0.ip_data))
+            .field("protocol", &self.0.protocol)
+            .field("proto_info", &Redacted(&self.0.proto_info))
+            .field("inner", &self.0.inner)
+            .field("inner_protocol", &self.0.inner_protocol)
+            .field("truncated", &self.0.truncated)
+            .field("timestamp", &self.0.timestamp)
+            .finish()
+    }
+}
+
+impl Display for Redacted<&FwLog> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let log = self.0;
+        write!(
+            f,
+            "rule {} {:?} {:?} on {} {:?} {} -> {}",
+            log.packet_filter.rule_info.number,
+            log.packet_filter.action,
+            log.packet_filter.dir,
+            log.packet_filter.interface,
+            log.protocol.name,
+            mask_addr(&log.ip_data.src),
+            mask_addr(&log.ip_data.dst),
+        )
+    }
+}
+
+impl FwLog {
+    /// Returns a view of this log whose `Debug`/`Display` output masks IP addresses.
+    pub fn redacted(&self) -> Redacted<&FwLog> {
+        Redacted(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_log;
+
+    const UDP_LOG: &str = "96,,,fae559338f65e11c53669fc3642c93c2,vlan0.20,match,pass,out,\
+        4,0x0,,127,58940,0,none,17,udp,\
+        106,192.168.10.15,192.168.20.11,49678,161,86";
+
+    #[test]
+    fn mask_addr_test() {
+        assert_eq!("192.168.x.x", mask_addr(&"192.168.10.15".parse().unwrap()));
+        assert_eq!(
+            "2001:db8:x:x:x:x:x:x",
+            mask_addr(&"2001:0db8:85a3::8a2e:0370:7334".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn redacted_debug_hides_addresses() {
+        let log = parse_log(UDP_LOG).unwrap();
+        let debug = format!("{:?}", log.redacted());
+        assert!(debug.contains("src: 192.168.x.x"));
+        assert!(debug.contains("dst: 192.168.x.x"));
+        assert!(!debug.contains("192.168.10.15"));
+        assert!(!debug.contains("192.168.20.11"));
+
+        //The regular Debug output is unchanged
+        assert!(format!("{:?}", log).contains("192.168.10.15"));
+    }
+
+    #[test]
+    fn redacted_debug_hides_embedded_address() {
+        let log = parse_log(
+            "97,,,02f4bab031b57d1e30553ce08e0ec131,em0,match,pass,out,\
+            4,0x0,,64,12345,0,none,1,icmp,\
+            56,192.168.1.1,10.0.0.5,unreachport,10.0.0.1,17,53",
+        )
+        .unwrap();
+        let debug = format!("{:?}", log.redacted());
+        assert!(debug.contains("dst: 10.0.x.x, proto_num: Some(17)"));
+        assert!(!debug.contains("10.0.0.1"));
+    }
+
+    #[test]
+    fn redacted_display_hides_addresses() {
+        let log = parse_log(UDP_LOG).unwrap();
+        assert_eq!(
+            "rule 96 Pass Out on vlan0.20 Udp 192.168.x.x -> 192.168.x.x",
+            log.redacted().to_string()
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Serde helper rendering a `u8` as a `0x`-prefixed hex string, as it appears in the logs.
+//!
+//! Enabled by the `serde-hex` feature; without it the fields serialize as plain integers.
+//! Deserialization accepts both the hex string and a plain integer.
+use std::fmt;
+
+use serde::{
+    de::{self, Visitor},
+    Deserializer, Serializer,
+};
+
+pub(crate) fn serialize<S: Serializer>(value: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{:02x}", value))
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    deserializer.deserialize_any(HexU8Visitor)
+}
+
+struct HexU8Visitor;
+
+impl<'de> Visitor<'de> for HexU8Visitor {
+    type Value = u8;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a hex string like 0x2f or an integer between 0 and 255")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u8, E> {
+        u8::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<u8, E> {
+        u8::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<u8, E> {
+        let parsed = match v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")) {
+            Some(hex) => u8::from_str_radix(hex, 16),
+            None => v.parse(),
+        };
+        parsed.map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::de::{value::Error, IntoDeserializer};
+
+    fn from_value<'a, T: IntoDeserializer<'a, Error>>(value: T) -> Result<u8, Error> {
+        deserialize(value.into_deserializer())
+    }
+
+    #[test]
+    fn deserialize_hex_or_integer() {
+        assert_eq!(Ok(0x2f), from_value("0x2f"));
+        assert_eq!(Ok(0x2f), from_value("0X2F"));
+        assert_eq!(Ok(47), from_value("47"));
+        assert_eq!(Ok(47), from_value(47u64));
+        assert_eq!(Ok(47), from_value(47i64));
+        assert!(from_value(256u64).is_err());
+        assert!(from_value(-1i64).is_err());
+        assert!(from_value("0xzz").is_err());
+    }
+}
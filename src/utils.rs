@@ -1,8 +1,12 @@
+use std::net::SocketAddrV4;
+use std::str::FromStr;
+use std::time::Duration;
+
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while},
     character::complete::{char, one_of},
-    combinator::{map_res, recognize},
+    combinator::{map_opt, opt, peek, recognize},
     multi::many1,
     sequence::{preceded, terminated},
     IResult, Parser,
@@ -15,23 +19,144 @@ where
     terminated(parser, char(',')) // applies the parser followed by a comma
 }
 
+/// Applies `parser` to an optional field, reading a lone `-` placeholder as absent like an empty field.
+pub(crate) fn opt_field<'a, F, O>(parser: F) -> impl FnMut(&'a str) -> IResult<&'a str, Option<O>>
+where
+    F: Parser<&'a str, O, nom::error::Error<&'a str>>,
+{
+    alt((
+        terminated(char('-'), peek(char(','))).map(|_| None),
+        opt(parser),
+    ))
+}
+
 pub(crate) fn parse_utf8_string(input: &str) -> IResult<&str, String> {
     take_while(|c| c != ',')
         .map(|s: &str| s.into())
         .parse(input)
 }
 
-pub(crate) fn hexadecimal_value(input: &str) -> IResult<&str, u8> {
-    map_res(
+/// Trims leading and trailing ASCII whitespace from every comma separated field.
+pub(crate) fn trim_fields(input: &str) -> String {
+    input
+        .split(',')
+        .map(|field| field.trim_matches(|c: char| c.is_ascii_whitespace()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+///Marker appended by syslog transports that cut the message short.
+const TRUNCATION_MARKER: &str = "[truncated]";
+
+///Ellipsis some transports append instead, which may also end real data.
+const ELLIPSIS_MARKER: &str = "...";
+
+/// Strips a trailing truncation marker, returning the line without it and whether it was found.
+///
+/// A trailing `...` is only read as a marker when `ellipsis` is set.
+pub(crate) fn strip_truncation_marker(input: &str, ellipsis: bool) -> (&str, bool) {
+    let trimmed = input.trim_end();
+    trimmed
+        .strip_suffix(TRUNCATION_MARKER)
+        .or_else(|| trimmed.strip_suffix(ELLIPSIS_MARKER).filter(|_| ellipsis))
+        .map_or((input, false), |line| (line.trim_end(), true))
+}
+
+///Strips one `vlan N` or hexadecimal ethertype (`0x8100`) token, with its separator, from the start of `input`.
+fn strip_link_layer_token(input: &str) -> Option<&str> {
+    let token_end = input.find([',', ' ']).unwrap_or(input.len());
+    let (token, next) = input.split_at(token_end);
+
+    let next = if token == "vlan" {
+        let next = next.strip_prefix(' ')?;
+        let id_end = next.find([',', ' ']).unwrap_or(next.len());
+        let (id, next) = next.split_at(id_end);
+        id.parse::<u16>().ok()?;
+        next
+    } else {
+        let hex = token.strip_prefix("0x")?;
+        u16::from_str_radix(hex, 16).ok()?;
+        next
+    };
+
+    Some(next.strip_prefix([',', ' ']).unwrap_or(next))
+}
+
+/// Strips the link-layer tokens (`vlan N`, hexadecimal ethertype) some sources prepend to the pf payload.
+pub(crate) fn strip_link_layer_prefix(mut input: &str) -> &str {
+    while let Some(next) = strip_link_layer_token(input) {
+        input = next;
+    }
+    input
+}
+
+/// Splits a leading `seconds.fraction` epoch timestamp field from `input`, returning it
+/// with the rest of the line. The fraction is required, so a rule number is never mistaken for it.
+///
+/// Returns `None` and the untouched input when the first field is not a timestamp.
+pub(crate) fn split_leading_timestamp(input: &str) -> (Option<Duration>, &str) {
+    let parsed = input.split_once(',').and_then(|(field, next)| {
+        let (secs, fraction) = field.split_once('.')?;
+        let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(secs) || !all_digits(fraction) || fraction.len() > 9 {
+            return None;
+        }
+        //Pad the fraction to nanoseconds, 0.5 is 500000000ns
+        let nanos = format!("{:0<9}", fraction).parse().ok()?;
+        Some((Duration::new(secs.parse().ok()?, nanos), next))
+    });
+
+    match parsed {
+        Some((timestamp, next)) => (Some(timestamp), next),
+        None => (None, input),
+    }
+}
+
+/// Splits the first pair of adjacent `address:port` IPv4 fields into the
+/// separate `src,dst,srcport,dstport` columns of the filterlog layout.
+///
+/// Returns `None` when there is no such pair.
+pub(crate) fn split_addr_ports(input: &str) -> Option<String> {
+    let mut fields: Vec<&str> = input.split(',').collect();
+    let position = fields.windows(2).position(|pair| {
+        pair.iter()
+            .all(|field| SocketAddrV4::from_str(field).is_ok())
+    })?;
+
+    //Both fields were just parsed as socket addresses, so they contain a ':'
+    let (src, srcport) = fields[position].split_once(':')?;
+    let (dst, dstport) = fields[position + 1].split_once(':')?;
+    fields.splice(position..position + 2, [src, dst, srcport, dstport]);
+
+    Some(fields.join(","))
+}
+
+///Parses a `0x`-prefixed hex value, failing when it doesn't fit in `T`.
+pub(crate) fn hexadecimal_value<T: TryFrom<u32>>(input: &str) -> IResult<&str, T> {
+    map_opt(
         preceded(
             alt((tag("0x"), tag("0X"))),
             recognize(many1(one_of("0123456789abcdefABCDEF"))),
         ),
-        |out: &str| u8::from_str_radix(out, 16),
+        |out: &str| {
+            u32::from_str_radix(out, 16)
+                .ok()
+                .and_then(|value| T::try_from(value).ok())
+        },
     )
     .parse(input)
 }
 
+///Parses a `u16` logged as `0x`-prefixed hex or as decimal, as exporters render the IPv4 id either way.
+pub(crate) fn hex_or_decimal_u16(input: &str) -> IResult<&str, u16> {
+    alt((hexadecimal_value, nom::character::complete::u16)).parse(input)
+}
+
+///Parses the TOS/traffic class: `0x`-prefixed hex, or a bare decimal as some exporters log it.
+pub(crate) fn tos_value(input: &str) -> IResult<&str, u8> {
+    alt((hexadecimal_value, nom::character::complete::u8)).parse(input)
+}
+
 #[cfg(test)]
 mod test {
     use nom::character::complete::u32;
@@ -42,4 +167,99 @@ mod test {
     fn test_csv() {
         assert_eq!(Ok(("other...", 10)), csv(u32)("10,other..."));
     }
+
+    #[test]
+    fn test_trim_fields() {
+        assert_eq!(
+            "pass,,tcp,igb0 (WAN)",
+            trim_fields(" pass,  , tcp\t,igb0 (WAN) ")
+        );
+    }
+
+    #[test]
+    fn test_strip_truncation_marker() {
+        assert_eq!(
+            ("49678,161,86", false),
+            strip_truncation_marker("49678,161,86", false)
+        );
+        assert_eq!(
+            ("49678,161,86...", false),
+            strip_truncation_marker("49678,161,86...", false)
+        );
+        assert_eq!(
+            ("49678,161,86", true),
+            strip_truncation_marker("49678,161,86...", true)
+        );
+        assert_eq!(
+            ("mss;nop", true),
+            strip_truncation_marker("mss;nop [truncated]\n", false)
+        );
+    }
+
+    #[test]
+    fn test_strip_link_layer_prefix() {
+        assert_eq!("96,,,label", strip_link_layer_prefix("vlan 20,96,,,label"));
+        assert_eq!("96,,,label", strip_link_layer_prefix("vlan 20 96,,,label"));
+        assert_eq!(
+            "96,,,label",
+            strip_link_layer_prefix("0x8100 vlan 20,96,,,label")
+        );
+        assert_eq!("96,,,label", strip_link_layer_prefix("0x0800,96,,,label"));
+        assert_eq!("96,,,label", strip_link_layer_prefix("96,,,label"));
+        assert_eq!("vlan x,96", strip_link_layer_prefix("vlan x,96"));
+    }
+
+    #[test]
+    fn test_split_addr_ports() {
+        assert_eq!(
+            Some("52,192.168.10.15,192.168.20.14,52461,9100,0,S".into()),
+            split_addr_ports("52,192.168.10.15:52461,192.168.20.14:9100,0,S")
+        );
+        assert_eq!(
+            None,
+            split_addr_ports("52,192.168.10.15,192.168.20.14,52461,9100")
+        );
+        //IPv6 addresses are never split
+        assert_eq!(None, split_addr_ports("60,2001:db8::1,2001:db8::2,"));
+    }
+
+    #[test]
+    fn test_split_leading_timestamp() {
+        assert_eq!(
+            (Some(Duration::new(1718187322, 123456000)), "96,,,"),
+            split_leading_timestamp("1718187322.123456,96,,,")
+        );
+        assert_eq!((None, "96,,,"), split_leading_timestamp("96,,,"));
+        assert_eq!((None, "1.x,96"), split_leading_timestamp("1.x,96"));
+        assert_eq!(
+            (None, "1.0123456789,96"),
+            split_leading_timestamp("1.0123456789,96")
+        );
+    }
+
+    #[test]
+    fn test_opt_field() {
+        assert_eq!(Ok((",next", Some(10))), opt_field(u32)("10,next"));
+        assert_eq!(Ok((",next", None)), opt_field(u32)(",next"));
+        assert_eq!(Ok((",next", None)), opt_field(u32)("-,next"));
+        //A dash only counts as a placeholder when it is the whole field
+        assert_eq!(Ok(("-1,next", None)), opt_field(u32)("-1,next"));
+    }
+
+    #[test]
+    fn test_hex_or_decimal_u16() {
+        assert_eq!(Ok((",", 61633)), hex_or_decimal_u16("61633,"));
+        assert_eq!(Ok((",", 61633)), hex_or_decimal_u16("0xF0C1,"));
+        assert_eq!(Ok((",", 61633)), hex_or_decimal_u16("0xf0c1,"));
+        assert!(csv(hex_or_decimal_u16)("0x1F0C1,").is_err());
+    }
+
+    #[test]
+    fn test_tos_value() {
+        assert_eq!(Ok(("", 0)), tos_value("0x0"));
+        assert_eq!(Ok(("", 47)), tos_value("0x2f"));
+        assert_eq!(Ok(("", 47)), tos_value("47"));
+        assert_eq!(Ok((",", 0)), tos_value("0,"));
+        assert!(csv(tos_value)("0xzz,").is_err());
+    }
 }
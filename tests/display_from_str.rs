@@ -0,0 +1,65 @@
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+use senpa::packet_filter::{Reason, RejectMethod};
+use senpa::{Action, Dir, ProtoName};
+
+//Every variant must format to a string its parser reads back
+fn assert_symmetric<T>(variants: &[T])
+where
+    T: Display + FromStr + PartialEq + Debug,
+    <T as FromStr>::Err: Debug,
+{
+    for variant in variants {
+        let formatted = variant.to_string();
+        assert_eq!(
+            variant,
+            &formatted.parse::<T>().unwrap(),
+            "{} does not parse back",
+            formatted
+        );
+    }
+}
+
+#[test]
+fn action_symmetry() {
+    assert_symmetric(&[
+        Action::Pass,
+        Action::Block,
+        Action::Reject,
+        Action::Nat,
+        Action::Rdr,
+        Action::Binat,
+    ]);
+}
+
+#[test]
+fn reject_method_symmetry() {
+    assert_symmetric(&[RejectMethod::Rst, RejectMethod::Icmp, RejectMethod::Unknown]);
+}
+
+#[test]
+fn dir_symmetry() {
+    assert_symmetric(&[Dir::In, Dir::Out]);
+}
+
+#[test]
+fn reason_symmetry() {
+    let reasons: Vec<Reason> = (0..=u8::MAX).map_while(Reason::from_u8).collect();
+    assert_eq!(16, reasons.len());
+    assert_symmetric(&reasons);
+}
+
+#[test]
+fn proto_name_symmetry() {
+    assert_symmetric(&[
+        ProtoName::Tcp,
+        ProtoName::Udp,
+        ProtoName::Icmp,
+        ProtoName::Vrrp,
+        ProtoName::Pim,
+        ProtoName::Dccp,
+        ProtoName::Other("gre".into()),
+        ProtoName::Other("250".into()),
+    ]);
+}
@@ -25,6 +25,7 @@ fn test_parse_log() {
         Pass => println!("Action: pass"),
         Block => println!("Actiom: block"),
         Reject => println!("Action: reject"),
+        Nat | Rdr | Binat => println!("Action: translate"),
     }
 
     assert_eq!(Pass, parsed_log.packet_filter.action);
@@ -32,6 +33,10 @@ fn test_parse_log() {
     match &parsed_log.protocol.name {
         Tcp => println!("Proto: tcp"),
         Udp => println!("Proto: udp"),
+        Icmp => println!("Proto: icmp"),
+        Vrrp => println!("Proto: vrrp"),
+        Pim => println!("Proto: pim"),
+        Dccp => println!("Proto: dccp"),
         Other(other) => println!("Proto: {}", other),
     }
     assert_eq!(Tcp, parsed_log.protocol.name);
@@ -39,7 +44,18 @@ fn test_parse_log() {
     match &parsed_log.proto_info {
         UdpInfo(udp_info) => println!("ProtoInfo:{:#?}", udp_info),
         TcpInfo(tcp_info) => println!("ProtoInfo:{:#?}", tcp_info),
+        IcmpInfo(icmp_info) => println!("ProtoInfo:{:#?}", icmp_info),
+        VrrpInfo(vrrp_info) => println!("ProtoInfo:{:#?}", vrrp_info),
+        PimInfo(pim_info) => println!("ProtoInfo:{:#?}", pim_info),
+        DccpInfo(dccp_info) => println!("ProtoInfo:{:#?}", dccp_info),
         UnknownInfo(unknown) => println!("ProtoInfo: {}", unknown),
     }
     assert!(matches!(parsed_log.proto_info, TcpInfo(_)));
+
+    if let TcpInfo(tcp_info) = &parsed_log.proto_info {
+        assert_eq!(
+            "mss;nop;wscale;nop;nop;sackOK",
+            tcp_info.options.to_string()
+        );
+    }
 }